@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
-const COMMANDS: &[&str] = &["available_ports", "cancel_read", "close", "close_all", "force_close", "open", "read", "write", "write_binary"];
+const COMMANDS: &[&str] = &["available_ports", "available_ports_cached", "available_ports_detailed", "bytes_to_read", "bytes_to_write", "cancel_read", "clear_break", "clear_buffer", "close", "close_all", "connect_device", "flush", "force_close", "force_unlock", "get_last_frame", "get_latency_timer", "get_port_config", "get_port_settings", "get_timeout", "is_open", "list_events_for_port", "list_open_ports", "open", "open_autoconfig", "open_fd", "ping_device", "port_driver_info", "read", "read_available", "read_buffer_len", "read_control_signals", "read_once", "read_stats", "reconnect", "record_traffic", "recommended_chunk_size", "runtime_info", "send_break", "set_baud_rate", "set_break", "set_latency_timer", "set_read_target", "set_rs485", "set_timeout", "start_port_scan", "stop_and_join_reader", "stop_port_scan", "write", "write_and_read", "write_async", "write_base64", "write_binary", "write_data_terminal_ready", "write_hex", "write_line", "write_request_to_send", "write_status", "write_with_timeout"];
 
 fn main() {
     if let Err(error) = tauri_plugin::Builder::new(COMMANDS).try_build()
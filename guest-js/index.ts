@@ -14,6 +14,32 @@ export interface PortInfo {
   vid: "Unknown" | string;
 }
 
+export interface PortDetails {
+  port_type: "USB" | "Bluetooth" | "PCI" | "Unknown";
+  vid: number | null;
+  pid: number | null;
+  serial_number: string | null;
+  manufacturer: string | null;
+  product: string | null;
+}
+
+/** What commands reject with */
+export interface PluginError {
+  kind:
+    | "PortNotFound"
+    | "PortAlreadyOpen"
+    | "PortBusy"
+    | "NotOpen"
+    | "AlreadyReading"
+    | "LockPoisoned"
+    | "TimedOut"
+    | "Cancelled"
+    | "InvalidInput"
+    | "Io"
+    | "Other";
+  message: string;
+}
+
 export interface InvokeResult {
   code: number;
   message: string;
@@ -21,18 +47,211 @@ export interface InvokeResult {
 
 export interface ReadDataResult {
   size: number;
+  /** byte values, or a string when reading with an `encoding` other than `bytes` */
+  data: number[] | string;
+  /** offset/hex/ascii dump of `data`, when reading with `hexdump` */
+  hexdump?: string;
+  /** set when the frame was cut at `maxMessageSize` before its delimiter arrived */
+  truncated?: boolean;
+}
+
+export type PortEventType =
+  | "read"
+  | "disconnected"
+  | "error"
+  | "auto-closed"
+  | "stats"
+  | "break"
+  | "sequence-gap"
+  | "write-complete"
+  | "reconnecting"
+  | "reconnected"
+  | "reconnect-failed"
+  | "port-added"
+  | "port-removed";
+
+/** Result of `readStats` */
+export interface ReadTotals {
+  bytes_read: number;
+  frames_emitted: number;
+  read_errors: number;
+}
+
+export interface ReadStats {
+  bytes: number;
+  frames: number;
+  interval_ms: number;
+  bytes_per_second: number;
+}
+
+export type LengthPrefix = "u8" | "u16be" | "u16le" | "u32be" | "u32le";
+
+export interface SequenceGap {
+  expected: number;
+  received: number;
+}
+
+export type WriteStatus = "Pending" | { Done: number } | { Failed: string };
+
+export interface DisconnectEvent {
+  path: string;
+  reason: string;
+  /** what failed */
+  kind: "read" | "write" | "clone";
+}
+
+export interface WriteResult {
+  write_id: number;
+  status: WriteStatus;
+}
+
+/**
+ * Payload shared by every event the plugin emits.
+ * `payload` is a `ReadDataResult` for `read` events, `ReadStats` for `stats` events,
+ * `DisconnectEvent` for `disconnected` events, `SequenceGap` for `sequence-gap` events,
+ * `WriteResult` for `write-complete` events, `ReconnectAttempt` for `reconnecting` events,
+ * `ReconnectInfo` for `reconnected` events and a message string otherwise.
+ */
+export interface PortEvent<
+  T =
+    | ReadDataResult
+    | ReadStats
+    | DisconnectEvent
+    | SequenceGap
+    | WriteResult
+    | ReconnectAttempt
+    | ReconnectInfo
+    | PortDetails
+    | string
+> {
+  path: string;
+  event_type: PortEventType;
+  payload: T;
+}
+
+export interface PortSettings {
+  baud_rate: number;
+  data_bits: 5 | 6 | 7 | 8;
+  flow_control: null | "Software" | "Hardware";
+  parity: null | "Odd" | "Even" | "Mark" | "Space";
+  stop_bits: 1 | 2;
+  timeout_ms: number;
+}
+
+export interface RuntimeInfo {
+  version: string;
+  async_commands: boolean;
+  background_tasks: boolean;
+  decompress: boolean;
+  latency_timer: boolean;
+  mark_space_parity: boolean;
+}
+
+export interface ReconnectInfo {
+  path: string;
+  settings: PortSettings;
+}
+
+/**
+ * Backoff between automatic reconnection attempts: the delay starts at `initial_ms`
+ * (500 by default) and is multiplied by `multiplier` (2 by default) up to `max_ms`
+ * (30000 by default). Without `max_attempts` it retries until reconnected.
+ */
+export interface ReconnectPolicy {
+  initial_ms?: number;
+  max_ms?: number;
+  multiplier?: number;
+  max_attempts?: number;
+}
+
+/** sets a control line to `level`, then waits `delay_ms` */
+export interface SignalStep {
+  line: "dtr" | "rts";
+  level: boolean;
+  delay_ms: number;
+}
+
+/** the handshake performed by `connectDevice` */
+export interface DeviceConfig {
+  baud_rate: number;
+  data_bits?: 5 | 6 | 7 | 8;
+  flow_control?: null | "Software" | "Hardware";
+  parity?: null | "Odd" | "Even" | "Mark" | "Space";
+  stop_bits?: 1 | 2;
+  timeout?: number;
+  signals?: SignalStep[];
+  /** bytes written once the port is open */
+  init?: number[];
+  /** regex the response to `init` must match */
+  expect?: string;
+  response_timeout_ms?: number;
+}
+
+export interface ConnectInfo {
+  settings: PortSettings;
+  response: number[];
+}
+
+export interface ControlSignals {
+  cts: boolean;
+  dsr: boolean;
+  carrier_detect: boolean;
+  ring_indicator: boolean;
+}
+
+export interface WriteAndRead {
+  /** number of bytes written */
+  written: number;
+  response: number[];
+}
+
+export interface ReconnectAttempt {
+  attempt: number;
+  delay_ms: number;
+}
+
+export interface TrafficChunk {
+  /** milliseconds since the recording started */
+  offset_ms: number;
   data: number[];
 }
 
+export interface PingStats {
+  samples: number;
+  min_us: number;
+  avg_us: number;
+  max_us: number;
+}
+
+export interface ChunkSizeHint {
+  platform: string;
+  transport: "events";
+  chunk_size: number;
+}
+
 export interface SerialportOptions {
   path: string;
   baudRate: number;
   encoding?: string;
   dataBits?: 5 | 6 | 7 | 8;
   flowControl?: null | "Software" | "Hardware";
-  parity?: null | "Odd" | "Even";
+  parity?: null | "Odd" | "Even" | "Mark" | "Space";
+  /** 1 by default */
   stopBits?: 1 | 2;
   timeout?: number;
+  autoCloseAfterMs?: number;
+  /** reject blocking commands that take longer than this with a timeout error */
+  commandTimeoutMs?: number;
+  /** prepend a header in this format holding the payload length to every write */
+  lengthPrefix?: LengthPrefix;
+  /** reopen the port automatically when it is lost while reading */
+  autoReconnect?: ReconnectPolicy;
+  /** control line changes performed right after the port opens */
+  onOpenSequence?: SignalStep[];
+  /** set to false to let other processes open the port too, ignored on Windows */
+  exclusive?: boolean;
+  /** raise RTS around every write to drive an RS485 transceiver */
+  rs485?: boolean;
   size?: number;
   is_test?: boolean;
   [key: string]: any;
@@ -41,17 +260,66 @@ export interface SerialportOptions {
 export interface Options {
   path?: string;
   baudRate?: number;
-  dataBits: 5 | 6 | 7 | 8;
-  flowControl: null | "Software" | "Hardware";
-  parity: null | "Odd" | "Even";
-  stopBits: 1 | 2;
-  timeout: number;
+  dataBits?: 5 | 6 | 7 | 8;
+  flowControl?: null | "Software" | "Hardware";
+  parity?: null | "Odd" | "Even" | "Mark" | "Space";
+  stopBits?: 1 | 2;
+  timeout?: number;
+  autoCloseAfterMs?: number;
+  commandTimeoutMs?: number;
+  lengthPrefix?: LengthPrefix;
+  autoReconnect?: ReconnectPolicy;
+  onOpenSequence?: SignalStep[];
+  exclusive?: boolean;
+  rs485?: boolean;
   [key: string]: any;
 }
 
 export interface ReadOptions {
+  /** read timeout of the reader in milliseconds, which bounds each blocking read but not framing */
   timeout?: number;
+  /**
+   * chunk size in raw mode and frame size in fixed mode, 1024 by default. Without `readMode`,
+   * `delimiter` or `lengthPrefix`, setting it selects fixed mode.
+   */
   size?: number;
+  /**
+   * `line` splits frames by the delimiter or length header (the default), `raw` emits every
+   * chunk as it arrives and `fixed` emits frames of exactly `size` bytes
+   */
+  readMode?: "line" | "raw" | "fixed";
+  /** bytes that end a frame, a newline by default */
+  delimiter?: Uint8Array | number[];
+  /** keep the delimiter at the end of each frame, true by default */
+  includeDelimiter?: boolean;
+  /** emit a partial frame once its first byte has waited this long */
+  maxLatencyMs?: number;
+  /** emit a `stats` event at this interval */
+  statsIntervalMs?: number;
+  /** include a hex dump of each frame */
+  hexdump?: boolean;
+  /** deliver the read events only to the window with this label */
+  targetLabel?: string;
+  /** report NUL bytes as `break` events instead of data */
+  detectBreak?: boolean;
+  /** byte offset of a big-endian sequence number in each frame */
+  sequenceOffset?: number;
+  /** length of the sequence number in bytes (1-8), 1 by default */
+  sequenceLength?: number;
+  /** split frames by a length header in this format instead of the delimiter */
+  lengthPrefix?: LengthPrefix;
+  /** largest payload a length header may announce, 64 KiB by default */
+  maxFrameLen?: number;
+  /** largest frame buffered while waiting for the delimiter, 1 MiB by default */
+  maxMessageSize?: number;
+  /** byte values dropped from the stream before framing */
+  stripBytes?: Uint8Array | number[];
+  /** keep the most recent frame for `getLastFrame` */
+  retainLastFrame?: boolean;
+  /** decompress each frame, requires the plugin's `decompress` feature */
+  decompress?: "gzip" | "zlib" | "deflate";
+  /** emit frames as byte arrays (the default), strings of hex digits or base64 strings */
+  encoding?: "bytes" | "hex" | "base64";
 }
 
 let tester_ports: { [key: string]: SerialPort } = {};
@@ -79,11 +347,19 @@ class SerialPort {
     this.options = {
       path: options.path,
       baudRate: options.baudRate,
-      dataBits: options.dataBits || 8,
-      flowControl: options.flowControl || null,
-      parity: options.parity || null,
-      stopBits: options.stopBits || 2,
-      timeout: options.timeout || 200,
+      // left unset so the defaults configured in the plugin apply
+      dataBits: options.dataBits,
+      flowControl: options.flowControl,
+      parity: options.parity,
+      stopBits: options.stopBits,
+      timeout: options.timeout,
+      autoCloseAfterMs: options.autoCloseAfterMs,
+      commandTimeoutMs: options.commandTimeoutMs,
+      lengthPrefix: options.lengthPrefix,
+      autoReconnect: options.autoReconnect,
+      onOpenSequence: options.onOpenSequence,
+      exclusive: options.exclusive,
+      rs485: options.rs485,
     };
     this.size = options.size || 1024;
     this.is_test = options.is_test || false;
@@ -91,12 +367,16 @@ class SerialPort {
 
   /**
    * @description: Get serial port list
+   * @param {boolean} includeAll list Bluetooth, PCI and unknown ports as well as USB ports
    * @return {Promise<string[]>}
    */
-  static async available_ports(): Promise<{ [key: string]: PortInfo }> {
+  static async available_ports(
+    includeAll?: boolean
+  ): Promise<{ [key: string]: PortInfo }> {
     try {
       const result = await invoke<{ [key: string]: PortInfo }>(
-        "plugin:serialplugin|available_ports"
+        "plugin:serialplugin|available_ports",
+        { includeAll }
       );
       for (const path in tester_ports) {
         result[path] = {
@@ -114,6 +394,163 @@ class SerialPort {
     }
   }
 
+  /**
+   * @description: Get serial port list with typed details, missing values are null
+   * @param {boolean} includeAll list Bluetooth, PCI and unknown ports as well as USB ports
+   * @return {Promise<{ [key: string]: PortDetails }>}
+   */
+  static async available_ports_detailed(
+    includeAll?: boolean
+  ): Promise<{ [key: string]: PortDetails }> {
+    return await invoke<{ [key: string]: PortDetails }>(
+      "plugin:serialplugin|available_ports_detailed",
+      { includeAll }
+    );
+  }
+
+  /**
+   * @description: Get serial port list, reusing the previous result while it is younger than `ttlMs`
+   * @param {number} ttlMs
+   * @param {boolean} includeAll list Bluetooth, PCI and unknown ports as well as USB ports
+   * @return {Promise<{ [key: string]: PortInfo }>}
+   */
+  static async available_ports_cached(
+    ttlMs?: number,
+    includeAll?: boolean
+  ): Promise<{ [key: string]: PortInfo }> {
+    return await invoke<{ [key: string]: PortInfo }>(
+      "plugin:serialplugin|available_ports_cached",
+      { ttlMs, includeAll }
+    );
+  }
+
+  /**
+   * @description: List the ports the plugin holds open with their current settings
+   * @return {Promise<{ [key: string]: PortSettings | null }>}
+   */
+  static async listOpenPorts(): Promise<{ [key: string]: PortSettings | null }> {
+    return await invoke<{ [key: string]: PortSettings | null }>(
+      "plugin:serialplugin|list_open_ports"
+    );
+  }
+
+  /**
+   * @description: Watch for ports being plugged in and out, replacing a running scan
+   * @param {number} intervalMs how often the ports are enumerated, 1000 ms by default
+   * @param {boolean} includeAll watch Bluetooth, PCI and unknown ports as well as USB ports
+   * @return {Promise<void>}
+   */
+  static async startPortScan(
+    intervalMs?: number,
+    includeAll?: boolean
+  ): Promise<void> {
+    return await invoke<void>("plugin:serialplugin|start_port_scan", {
+      intervalMs,
+      includeAll,
+    });
+  }
+
+  /**
+   * @description: Stop the scan started by `startPortScan`
+   * @return {Promise<void>}
+   */
+  static async stopPortScan(): Promise<void> {
+    return await invoke<void>("plugin:serialplugin|stop_port_scan");
+  }
+
+  /**
+   * @description: Listen for ports plugged in while a port scan is running
+   * @param {function} fn called with the port's path and details
+   * @return {Promise<UnlistenFn>}
+   */
+  static async listenPortAdded(
+    fn: (path: string, details: PortDetails) => void
+  ): Promise<UnlistenFn> {
+    return await appWindow.listen<PortEvent<PortDetails>>(
+      "plugin-serialport-port-added",
+      ({ payload }) => fn(payload.path, payload.payload)
+    );
+  }
+
+  /**
+   * @description: Listen for ports unplugged while a port scan is running
+   * @param {function} fn called with the port's path and last known details
+   * @return {Promise<UnlistenFn>}
+   */
+  static async listenPortRemoved(
+    fn: (path: string, details: PortDetails) => void
+  ): Promise<UnlistenFn> {
+    return await appWindow.listen<PortEvent<PortDetails>>(
+      "plugin-serialport-port-removed",
+      ({ payload }) => fn(payload.path, payload.payload)
+    );
+  }
+
+  /**
+   * @description: List the names of all events the plugin can emit for a port
+   * @param {string} path
+   * @return {Promise<string[]>}
+   */
+  static async listEventsForPort(path: string): Promise<string[]> {
+    return await invoke<string[]>("plugin:serialplugin|list_events_for_port", {
+      path,
+    });
+  }
+
+  /**
+   * @description: Get the suggested number of bytes per read event on this platform
+   * @return {Promise<ChunkSizeHint>}
+   */
+  static async recommendedChunkSize(): Promise<ChunkSizeHint> {
+    return await invoke<ChunkSizeHint>(
+      "plugin:serialplugin|recommended_chunk_size"
+    );
+  }
+  /**
+   * @description: Report what this build of the plugin supports
+   * @return {Promise<RuntimeInfo>}
+   */
+  static async runtimeInfo(): Promise<RuntimeInfo> {
+    return await invoke<RuntimeInfo>("plugin:serialplugin|runtime_info");
+  }
+
+
+  /**
+   * @description: Get the platform and driver details of a port
+   * @param {string} path
+   * @return {Promise<{ [key: string]: string }>}
+   */
+  static async driverInfo(path: string): Promise<{ [key: string]: string }> {
+    return await invoke<{ [key: string]: string }>(
+      "plugin:serialplugin|port_driver_info",
+      { path }
+    );
+  }
+
+  /**
+   * @description: Get the latency timer of an FTDI adapter in milliseconds
+   * @param {string} path
+   * @return {Promise<number>}
+   */
+  static async getLatencyTimer(path: string): Promise<number> {
+    return await invoke<number>("plugin:serialplugin|get_latency_timer", {
+      path,
+    });
+  }
+
+  /**
+   * @description: Set the latency timer of an FTDI adapter in milliseconds (1-255)
+   * @param {string} path
+   * @param {number} value
+   * @return {Promise<void>}
+   */
+  static async setLatencyTimer(path: string, value: number): Promise<void> {
+    return await invoke<void>("plugin:serialplugin|set_latency_timer", {
+      path,
+      value,
+    });
+  }
+
   /**
    * @description: force close
    * @param {string} path
@@ -129,6 +566,17 @@ class SerialPort {
     });
   }
 
+  /**
+   * @description: Try to make a port locked by a crashed session usable again (best effort)
+   * @param {string} path
+   * @return {Promise<boolean>} whether the port could be opened afterwards
+   */
+  static async forceUnlock(path: string): Promise<boolean> {
+    return await invoke<boolean>("plugin:serialplugin|force_unlock", {
+      path,
+    });
+  }
+
   /**
    * @description: close all serial ports
    * @return {Promise<void>}
@@ -176,129 +624,498 @@ class SerialPort {
   }
 
   /**
-   * @description:
-   * @param {object} options
-   * @return {Promise<void>}
+   * @description: Close and reopen the port with its original settings, restarting an active reader
+   * @return {Promise<ReconnectInfo>}
    */
-  async change(options: { path?: string; baudRate?: number }): Promise<void> {
+  async reconnect(): Promise<ReconnectInfo> {
     try {
-      let isOpened = false;
-      if (this.isOpen) {
-        isOpened = true;
-        await this.close();
-      }
-      if (options.path) {
-        this.options.path = options.path;
-      }
-      if (options.baudRate) {
-        this.options.baudRate = options.baudRate;
-      }
-      if (isOpened) {
-        await this.open();
-      }
-      return Promise.resolve();
+      const info = await invoke<ReconnectInfo>("plugin:serialplugin|reconnect", {
+        path: this.options.path,
+      });
+      this.isOpen = true;
+      return info;
     } catch (error) {
       return Promise.reject(error);
     }
   }
 
   /**
-   * @description: close the serial port
-   * @return {Promise<InvokeResult>}
+   * @description: Record the raw bytes received for a while, pausing a running reader meanwhile
+   * @param {number} durationMs
+   * @return {Promise<TrafficChunk[]>}
    */
-  async close(): Promise<void> {
+  async recordTraffic(durationMs: number): Promise<TrafficChunk[]> {
     try {
-      if (!this.isOpen) {
-        return;
-      }
-      await this.cancelRead();
-      let res = undefined;
-      if (!this.is_test) {
-        res = await invoke<void>("plugin:serialplugin|close", {
-          path: this.options.path,
-        });
-      }
-
-      await this.cancelListen();
-      this.isOpen = false;
-      return res;
+      return await invoke<TrafficChunk[]>("plugin:serialplugin|record_traffic", {
+        path: this.options.path,
+        durationMs,
+      });
     } catch (error) {
       return Promise.reject(error);
     }
   }
 
-  async disconnected(fn: (...args: any[]) => void): Promise<void> {
-    let sub_path = this.options.path?.toString().replace(/\.+/, "");
-    let checkEvent = `plugin-serialport-disconnected-${sub_path}`;
-    console.log("listen event: " + checkEvent);
-    let unListen: any = await appWindow.listen<ReadDataResult>(
-      checkEvent,
-      () => {
-        try {
-          fn();
-          unListen();
-          unListen = undefined;
-        } catch (error) {
-          console.error(error);
-        }
-      }
-    );
-  }
-
   /**
-   * @description: Monitor serial port information
-   * @param {function} fn
-   * @param isDecode
-   * @return {Promise<void>}
+   * @description: Get the most recent frame of a reader started with `retainLastFrame`
+   * @return {Promise<ReadDataResult | null>}
    */
-  async listen(
-    fn: (...args: any[]) => void,
-    isDecode = true
-  ): Promise<UnlistenFn> {
+  async getLastFrame(): Promise<ReadDataResult | null> {
     try {
-      await this.cancelListen();
-      let sub_path = this.options.path?.toString().replace(/\.+/, "");
-      let readEvent = `plugin-serialport-read-${sub_path}`;
-      console.log("listen event: " + readEvent);
-
-      const unListen = await appWindow.listen<ReadDataResult>(
-        readEvent,
-        ({ payload }) => {
-          try {
-            if (isDecode) {
-              const decoder = new TextDecoder(this.encoding);
-              const data = decoder.decode(new Uint8Array(payload.data));
-              fn(data);
-            } else {
-              fn(new Uint8Array(payload.data));
-            }
-          } catch (error) {
-            console.error(error);
-          }
+      return await invoke<ReadDataResult | null>(
+        "plugin:serialplugin|get_last_frame",
+        {
+          path: this.options.path,
         }
       );
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
 
-      this.unListen = unListen; // Assign the unListen function to the class property
+  /**
+   * @description: Get the settings the port was opened with, including later changes
+   * @return {Promise<PortSettings>}
+   */
+  async getPortConfig(): Promise<PortSettings> {
+    try {
+      return await invoke<PortSettings>("plugin:serialplugin|get_port_config", {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
 
-      return Promise.resolve(unListen); // Return the unListen function as a promise
+  /**
+   * @description: Read the current settings of the port back from the driver
+   * @return {Promise<PortSettings>}
+   */
+  async getPortSettings(): Promise<PortSettings> {
+    try {
+      return await invoke<PortSettings>("plugin:serialplugin|get_port_settings", {
+        path: this.options.path,
+      });
     } catch (error) {
-      return Promise.reject("Failed to monitor serial port data: " + error);
+      return Promise.reject(error);
     }
   }
 
   /**
-   * @description: open serial port
-   * @return {*}
+   * @description: Get the read timeout of the port in milliseconds
+   * @return {Promise<number>}
    */
-  async open(): Promise<void> {
+  async getTimeout(): Promise<number> {
     try {
-      if (!this.options.path) {
-        return Promise.reject(`path Can not be empty!`);
-      }
-      if (!this.options.baudRate) {
-        return Promise.reject(`baudRate Can not be empty!`);
-      }
-      if (this.isOpen) {
+      return await invoke<number>("plugin:serialplugin|get_timeout", {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Set the read timeout of the port in milliseconds
+   * @param {number} timeoutMs
+   * @return {Promise<void>}
+   */
+  async setTimeout(timeoutMs: number): Promise<void> {
+    try {
+      await invoke<void>("plugin:serialplugin|set_timeout", {
+        path: this.options.path,
+        timeoutMs,
+      });
+      this.options.timeout = timeoutMs;
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Change where the running reader emits its events
+   * @param {string} target `"global"`, `"caller"` or a window label
+   * @return {Promise<void>}
+   */
+  async setReadTarget(target: "global" | "caller" | string): Promise<void> {
+    try {
+      return await invoke<void>("plugin:serialplugin|set_read_target", {
+        path: this.options.path,
+        target,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Change the baud rate of the open port without closing it
+   * @param {number} baudRate
+   * @return {Promise<number>} the previous baud rate
+   */
+  async setBaudRateLive(baudRate: number): Promise<number> {
+    try {
+      const previous = await invoke<number>("plugin:serialplugin|set_baud_rate", {
+        path: this.options.path,
+        baudRate,
+      });
+      this.options.baudRate = baudRate;
+      return previous;
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Wait for data and return what a single read delivers, without starting a reader
+   * @param {number} size largest number of bytes to return, 1024 by default
+   * @param {number} timeout milliseconds to wait, the port's read timeout by default
+   * @return {Promise<Uint8Array>}
+   */
+  async readOnce(size?: number, timeout?: number): Promise<Uint8Array> {
+    try {
+      const data = await invoke<number[]>("plugin:serialplugin|read_once", {
+        path: this.options.path,
+        size,
+        timeout,
+      });
+      return new Uint8Array(data);
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Get the number of bytes waiting in the input buffer
+   * @return {Promise<number>}
+   */
+  async bytesToRead(): Promise<number> {
+    try {
+      return await invoke<number>("plugin:serialplugin|bytes_to_read", {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Wait until the written bytes have been transmitted
+   * @param {number} commandTimeoutMs fail with a `TimedOut` error if they aren't transmitted in time
+   * @return {Promise<void>}
+   */
+  async flush(commandTimeoutMs?: number): Promise<void> {
+    try {
+      return await invoke<void>("plugin:serialplugin|flush", {
+        path: this.options.path,
+        commandTimeoutMs,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Get the number of bytes waiting in the output buffer
+   * @return {Promise<number>}
+   */
+  async bytesToWrite(): Promise<number> {
+    try {
+      return await invoke<number>("plugin:serialplugin|bytes_to_write", {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Discard the bytes waiting in the input buffer, output buffer or both
+   * @param {"input" | "output" | "all"} bufferType
+   * @return {Promise<void>}
+   */
+  async clearBuffer(bufferType: "input" | "output" | "all"): Promise<void> {
+    try {
+      return await invoke<void>("plugin:serialplugin|clear_buffer", {
+        path: this.options.path,
+        bufferType,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Check whether the plugin holds the port open
+   * @return {Promise<boolean>}
+   */
+  async isPortOpen(): Promise<boolean> {
+    try {
+      return await invoke<boolean>("plugin:serialplugin|is_open", {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Read the bytes waiting in the input buffer without waiting for more
+   * @return {Promise<Uint8Array>} empty when nothing is waiting
+   */
+  async readAvailable(): Promise<Uint8Array> {
+    try {
+      const data = await invoke<number[]>("plugin:serialplugin|read_available", {
+        path: this.options.path,
+      });
+      return new Uint8Array(data);
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Get the totals of the reader since `read` was last called
+   * @return {Promise<ReadTotals>}
+   */
+  async readStats(): Promise<ReadTotals> {
+    try {
+      return await invoke<ReadTotals>("plugin:serialplugin|read_stats", {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Get the number of bytes the reader has buffered toward its next frame
+   * @return {Promise<number>}
+   */
+  async readBufferLen(): Promise<number> {
+    try {
+      return await invoke<number>("plugin:serialplugin|read_buffer_len", {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Stop reading and wait until the read thread has exited
+   * @return {Promise<void>}
+   */
+  async stopAndJoinReader(): Promise<void> {
+    if (this.is_test) {
+      return Promise.resolve();
+    }
+    try {
+      return await invoke<void>("plugin:serialplugin|stop_and_join_reader", {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description:
+   * @param {object} options
+   * @return {Promise<void>}
+   */
+  async change(options: { path?: string; baudRate?: number }): Promise<void> {
+    try {
+      let isOpened = false;
+      if (this.isOpen) {
+        isOpened = true;
+        await this.close();
+      }
+      if (options.path) {
+        this.options.path = options.path;
+      }
+      if (options.baudRate) {
+        this.options.baudRate = options.baudRate;
+      }
+      if (isOpened) {
+        await this.open();
+      }
+      return Promise.resolve();
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: close the serial port
+   * @return {Promise<InvokeResult>}
+   */
+  async close(): Promise<void> {
+    try {
+      if (!this.isOpen) {
+        return;
+      }
+      await this.cancelRead();
+      let res = undefined;
+      if (!this.is_test) {
+        res = await invoke<void>("plugin:serialplugin|close", {
+          path: this.options.path,
+        });
+      }
+
+      await this.cancelListen();
+      this.isOpen = false;
+      return res;
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  async disconnected(
+    fn: (disconnect: DisconnectEvent) => void
+  ): Promise<void> {
+    let sub_path = this.options.path?.toString().replace(/\.+/, "");
+    let checkEvent = `plugin-serialport-disconnected-${sub_path}`;
+    console.log("listen event: " + checkEvent);
+    let unListen: any = await appWindow.listen<PortEvent<DisconnectEvent>>(
+      checkEvent,
+      ({ payload }) => {
+        try {
+          fn(payload.payload);
+          unListen();
+          unListen = undefined;
+        } catch (error) {
+          console.error(error);
+        }
+      }
+    );
+  }
+
+  /**
+   * @description: Listen for the port being closed after `autoCloseAfterMs` of inactivity
+   * @param {function} fn
+   * @return {Promise<void>}
+   */
+  async autoClosed(fn: (...args: any[]) => void): Promise<void> {
+    let sub_path = this.options.path?.toString().replace(/\.+/, "");
+    let checkEvent = `plugin-serialport-auto-closed-${sub_path}`;
+    console.log("listen event: " + checkEvent);
+    let unListen: any = await appWindow.listen<PortEvent<string>>(checkEvent, () => {
+      try {
+        fn();
+        unListen();
+        unListen = undefined;
+      } catch (error) {
+        console.error(error);
+      }
+    });
+  }
+
+  /**
+   * @description: Monitor serial port information
+   * @param {function} fn
+   * @param isDecode
+   * @return {Promise<void>}
+   */
+  async listen(
+    fn: (...args: any[]) => void,
+    isDecode = true
+  ): Promise<UnlistenFn> {
+    try {
+      await this.cancelListen();
+      let sub_path = this.options.path?.toString().replace(/\.+/, "");
+      let readEvent = `plugin-serialport-read-${sub_path}`;
+      console.log("listen event: " + readEvent);
+
+      const unListen = await appWindow.listen<PortEvent<ReadDataResult>>(
+        readEvent,
+        ({ payload: { payload } }) => {
+          try {
+            if (typeof payload.data === "string") {
+              // already encoded as the read's `encoding` asked
+              fn(payload.data);
+            } else if (isDecode) {
+              const decoder = new TextDecoder(this.encoding);
+              const data = decoder.decode(new Uint8Array(payload.data));
+              fn(data);
+            } else {
+              fn(new Uint8Array(payload.data));
+            }
+          } catch (error) {
+            console.error(error);
+          }
+        }
+      );
+
+      this.unListen = unListen; // Assign the unListen function to the class property
+
+      return Promise.resolve(unListen); // Return the unListen function as a promise
+    } catch (error) {
+      return Promise.reject("Failed to monitor serial port data: " + error);
+    }
+  }
+
+  /**
+   * @description: Listen for the throughput statistics emitted when reading with `statsIntervalMs`
+   * @param {function} fn
+   * @return {Promise<UnlistenFn>}
+   */
+  async listenStats(fn: (stats: ReadStats) => void): Promise<UnlistenFn> {
+    let sub_path = this.options.path?.toString().replace(/\.+/, "");
+    let statsEvent = `plugin-serialport-stats-${sub_path}`;
+    console.log("listen event: " + statsEvent);
+    return await appWindow.listen<PortEvent<ReadStats>>(
+      statsEvent,
+      ({ payload }) => fn(payload.payload)
+    );
+  }
+
+  /**
+   * @description: Listen for the sequence gaps detected when reading with `sequenceOffset`
+   * @param {function} fn
+   * @return {Promise<UnlistenFn>}
+   */
+  async listenSequenceGap(
+    fn: (gap: SequenceGap) => void
+  ): Promise<UnlistenFn> {
+    let sub_path = this.options.path?.toString().replace(/\.+/, "");
+    let gapEvent = `plugin-serialport-sequence-gap-${sub_path}`;
+    console.log("listen event: " + gapEvent);
+    return await appWindow.listen<PortEvent<SequenceGap>>(
+      gapEvent,
+      ({ payload }) => fn(payload.payload)
+    );
+  }
+
+  /**
+   * @description: Listen for automatic reconnection attempts
+   * @param {function} fn called with each attempt before it is made
+   * @return {Promise<UnlistenFn>}
+   */
+  async listenReconnecting(
+    fn: (attempt: ReconnectAttempt) => void
+  ): Promise<UnlistenFn> {
+    let sub_path = this.options.path?.toString().replace(/\.+/, "");
+    let reconnectingEvent = `plugin-serialport-reconnecting-${sub_path}`;
+    console.log("listen event: " + reconnectingEvent);
+    return await appWindow.listen<PortEvent<ReconnectAttempt>>(
+      reconnectingEvent,
+      ({ payload }) => fn(payload.payload)
+    );
+  }
+
+  /**
+   * @description: open serial port
+   * @return {*}
+   */
+  async open(): Promise<void> {
+    try {
+      if (!this.options.path) {
+        return Promise.reject(`path Can not be empty!`);
+      }
+      if (!this.options.baudRate) {
+        return Promise.reject(`baudRate Can not be empty!`);
+      }
+      if (this.isOpen) {
         return;
       }
       let res = undefined;
@@ -313,6 +1130,11 @@ class SerialPort {
           parity: this.options.parity,
           stopBits: this.options.stopBits,
           timeout: this.options.timeout,
+          autoCloseAfterMs: this.options.autoCloseAfterMs,
+          autoReconnect: this.options.autoReconnect,
+          onOpenSequence: this.options.onOpenSequence,
+          exclusive: this.options.exclusive,
+          rs485: this.options.rs485,
         });
       }
 
@@ -321,12 +1143,146 @@ class SerialPort {
       this.disconnected(() => {
         this.isOpen = false;
       }).catch((err) => console.error(err));
+      if (this.options.autoCloseAfterMs) {
+        this.autoClosed(() => {
+          this.isOpen = false;
+        }).catch((err) => console.error(err));
+      }
       return Promise.resolve(res);
     } catch (error) {
       return Promise.reject(error);
     }
   }
 
+  /**
+   * @description: register an already open tty file descriptor under this port's path (Unix only)
+   * @param {number} fd the plugin takes ownership of the descriptor
+   * @return {Promise<void>}
+   */
+  async openFd(fd: number): Promise<void> {
+    try {
+      if (!this.options.path) {
+        return Promise.reject(`path Can not be empty!`);
+      }
+      if (this.isOpen) {
+        return;
+      }
+      await invoke<void>("plugin:serialplugin|open_fd", {
+        path: this.options.path,
+        fd,
+        baudRate: this.options.baudRate,
+        dataBits: this.options.dataBits,
+        flowControl: this.options.flowControl,
+        parity: this.options.parity,
+        stopBits: this.options.stopBits,
+        timeout: this.options.timeout,
+      });
+      this.isOpen = true;
+      return Promise.resolve();
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Open the port and perform a connection handshake, closing it again if any step fails
+   * @param {DeviceConfig} config
+   * @return {Promise<ConnectInfo>}
+   */
+  async connectDevice(config: DeviceConfig): Promise<ConnectInfo> {
+    try {
+      if (!this.options.path) {
+        return Promise.reject(`path Can not be empty!`);
+      }
+      const info = await invoke<ConnectInfo>(
+        "plugin:serialplugin|connect_device",
+        {
+          path: this.options.path,
+          config,
+        }
+      );
+      this.options.baudRate = info.settings.baud_rate;
+      this.isOpen = true;
+
+      this.disconnected(() => {
+        this.isOpen = false;
+      }).catch((err) => console.error(err));
+      return info;
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: open serial port and apply the settings declared in the device banner
+   * @param {string} pattern regex with the named groups baud_rate, data_bits, parity, stop_bits, flow_control
+   * @param {number} bannerTimeout how long to wait for the banner in milliseconds
+   * @return {Promise<PortSettings>}
+   */
+  async openAutoconfig(
+    pattern: string,
+    bannerTimeout?: number
+  ): Promise<PortSettings> {
+    try {
+      if (!this.options.path) {
+        return Promise.reject(`path Can not be empty!`);
+      }
+      const settings = await invoke<PortSettings>(
+        "plugin:serialplugin|open_autoconfig",
+        {
+          path: this.options.path,
+          pattern,
+          baudRate: this.options.baudRate,
+          timeout: this.options.timeout,
+          bannerTimeout,
+        }
+      );
+      this.options.baudRate = settings.baud_rate;
+      this.options.dataBits = settings.data_bits;
+      this.options.flowControl = settings.flow_control;
+      this.options.parity = settings.parity;
+      this.options.stopBits = settings.stop_bits;
+      this.isOpen = true;
+
+      this.disconnected(() => {
+        this.isOpen = false;
+      }).catch((err) => console.error(err));
+      return Promise.resolve(settings);
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Measure the round-trip latency to the device
+   * @param {Uint8Array | number[]} probe bytes to send
+   * @param {string} expect regex the response has to match
+   * @param {number} timeoutMs how long to wait for each response
+   * @param {number} samples number of round trips to measure
+   * @return {Promise<PingStats>}
+   */
+  async ping(
+    probe: Uint8Array | number[],
+    expect: string,
+    timeoutMs: number,
+    samples?: number
+  ): Promise<PingStats> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`serial port ${this.options.path} not opened!`);
+      }
+      return await invoke<PingStats>("plugin:serialplugin|ping_device", {
+        path: this.options.path,
+        probe: Array.from(probe),
+        expect,
+        timeoutMs,
+        samples,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
   /**
    * @description: Read serial port information
    * @param {ReadOptions} options read option { timeout, size }
@@ -343,7 +1299,24 @@ class SerialPort {
       return await invoke<void>("plugin:serialplugin|read", {
         path: this.options.path,
         timeout: options?.timeout || this.options.timeout,
-        size: options?.size || this.size,
+        size: options?.size,
+        delimiter: options?.delimiter ? Array.from(options.delimiter) : null,
+        includeDelimiter: options?.includeDelimiter,
+        maxLatencyMs: options?.maxLatencyMs,
+        statsIntervalMs: options?.statsIntervalMs,
+        hexdump: options?.hexdump,
+        targetLabel: options?.targetLabel,
+        detectBreak: options?.detectBreak,
+        sequenceOffset: options?.sequenceOffset,
+        sequenceLength: options?.sequenceLength,
+        lengthPrefix: options?.lengthPrefix,
+        maxFrameLen: options?.maxFrameLen,
+        maxMessageSize: options?.maxMessageSize,
+        stripBytes: options?.stripBytes ? Array.from(options.stripBytes) : null,
+        retainLastFrame: options?.retainLastFrame,
+        decompress: options?.decompress,
+        readMode: options?.readMode,
+        encoding: options?.encoding,
       });
     } catch (error) {
       return Promise.reject(error);
@@ -397,9 +1370,19 @@ class SerialPort {
   /**
    * @description: Serial port write data
    * @param {string} value
+   * @param {object} options { flush, lineEnding, complete } `flush` waits until the data has left
+   * the output buffer, `lineEnding` is appended to `value` and `complete` keeps writing until all of
+   * it is written
    * @return {Promise<number>}
    */
-  async write(value: string): Promise<number> {
+  async write(
+    value: string,
+    options?: {
+      flush?: boolean;
+      lineEnding?: "none" | "lf" | "cr" | "crlf";
+      complete?: boolean;
+    }
+  ): Promise<number> {
     try {
       if (!this.isOpen) {
         return Promise.reject(`serial port ${this.options.path} not opened!`);
@@ -412,6 +1395,11 @@ class SerialPort {
       return await invoke<number>("plugin:serialplugin|write", {
         value,
         path: this.options.path,
+        commandTimeoutMs: this.options.commandTimeoutMs,
+        lengthPrefix: this.options.lengthPrefix,
+        flush: options?.flush,
+        lineEnding: options?.lineEnding,
+        complete: options?.complete,
       });
     } catch (error) {
       return Promise.reject(error);
@@ -421,9 +1409,14 @@ class SerialPort {
   /**
    * @description: Write binary data to the serial port
    * @param {Uint8Array} value
+   * @param {object} options { flush, complete } `flush` waits until the data has left the output
+   * buffer, and all of `value` is written unless `complete` is false
    * @return {Promise<number>}
    */
-  async writeBinary(value: Uint8Array | number[]): Promise<number> {
+  async writeBinary(
+    value: Uint8Array | number[],
+    options?: { flush?: boolean; complete?: boolean }
+  ): Promise<number> {
     try {
       if (!this.isOpen) {
         return Promise.reject(`serial port ${this.options.path} not opened!`);
@@ -435,6 +1428,10 @@ class SerialPort {
         return await invoke<number>("plugin:serialplugin|write_binary", {
           value: Array.from(value),
           path: this.options.path,
+          commandTimeoutMs: this.options.commandTimeoutMs,
+          lengthPrefix: this.options.lengthPrefix,
+          flush: options?.flush,
+          complete: options?.complete,
         });
       } else {
         return Promise.reject(
@@ -445,6 +1442,285 @@ class SerialPort {
       return Promise.reject(error);
     }
   }
+  /**
+   * @description: Queue binary data for writing without waiting for the write to finish
+   * @param {Uint8Array | number[]} value
+   * @return {Promise<number>} the write id to pass to `writeStatus`
+   */
+  async writeAsync(value: Uint8Array | number[]): Promise<number> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`serial port ${this.options.path} not opened!`);
+      }
+      return await invoke<number>("plugin:serialplugin|write_async", {
+        value: Array.from(value),
+        path: this.options.path,
+        lengthPrefix: this.options.lengthPrefix,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write a command and read its response while holding the port, until the
+   * response ends with `delimiter` or is `size` bytes long
+   * @param {Uint8Array | number[]} value
+   * @param {object} options { delimiter, size, timeout }
+   * @return {Promise<WriteAndRead>}
+   */
+  async writeAndRead(
+    value: Uint8Array | number[],
+    options: {
+      delimiter?: Uint8Array | number[];
+      size?: number;
+      timeout?: number;
+    }
+  ): Promise<WriteAndRead> {
+    try {
+      return await invoke<WriteAndRead>("plugin:serialplugin|write_and_read", {
+        path: this.options.path,
+        value: Array.from(value),
+        delimiter: options.delimiter ? Array.from(options.delimiter) : null,
+        size: options.size,
+        timeout: options.timeout,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Read the CTS, DSR, CD and RI input lines
+   * @return {Promise<ControlSignals>}
+   */
+  async readControlSignals(): Promise<ControlSignals> {
+    try {
+      return await invoke<ControlSignals>("plugin:serialplugin|read_control_signals", {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Transmit a break condition
+   * @param {number} durationMs how long to hold the break, 250 ms by default
+   * @return {Promise<void>}
+   */
+  async sendBreak(durationMs?: number): Promise<void> {
+    try {
+      return await invoke<void>("plugin:serialplugin|send_break", {
+        path: this.options.path,
+        durationMs,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Start a break condition, held until `clearBreak`
+   * @return {Promise<void>}
+   */
+  async setBreak(): Promise<void> {
+    try {
+      return await invoke<void>("plugin:serialplugin|set_break", {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: End a break condition started by `setBreak`
+   * @return {Promise<void>}
+   */
+  async clearBreak(): Promise<void> {
+    try {
+      return await invoke<void>("plugin:serialplugin|clear_break", {
+        path: this.options.path,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Turn RS485 direction control on or off, raising RTS only while writing
+   * @param {boolean} enabled
+   * @return {Promise<void>}
+   */
+  async setRs485(enabled: boolean): Promise<void> {
+    try {
+      return await invoke<void>("plugin:serialplugin|set_rs485", {
+        path: this.options.path,
+        enabled,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Set the DTR control line
+   * @param {boolean} level
+   * @return {Promise<void>}
+   */
+  async writeDataTerminalReady(level: boolean): Promise<void> {
+    try {
+      return await invoke<void>("plugin:serialplugin|write_data_terminal_ready", {
+        path: this.options.path,
+        level,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Set the RTS control line
+   * @param {boolean} level
+   * @return {Promise<void>}
+   */
+  async writeRequestToSend(level: boolean): Promise<void> {
+    try {
+      return await invoke<void>("plugin:serialplugin|write_request_to_send", {
+        path: this.options.path,
+        level,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write bytes given as a hex string such as "01 03 00 00", ignoring whitespace
+   * @param {string} value
+   * @param {object} options { flush, complete } as for `writeBinary`
+   * @return {Promise<number>}
+   */
+  async writeHex(
+    value: string,
+    options?: { flush?: boolean; complete?: boolean }
+  ): Promise<number> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`serial port ${this.options.path} not opened!`);
+      }
+      return await invoke<number>("plugin:serialplugin|write_hex", {
+        value,
+        path: this.options.path,
+        commandTimeoutMs: this.options.commandTimeoutMs,
+        flush: options?.flush,
+        complete: options?.complete,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write a line of text followed by a terminator
+   * @param {string} value
+   * @param {string} terminator appended to `value`, "\n" by default
+   * @return {Promise<number>} the bytes written, including the terminator
+   */
+  async writeLine(value: string, terminator?: string): Promise<number> {
+    try {
+      if (!this.isOpen) {
+        return Promise.reject(`serial port ${this.options.path} not opened!`);
+      }
+      return await invoke<number>("plugin:serialplugin|write_line", {
+        value,
+        terminator,
+        path: this.options.path,
+        commandTimeoutMs: this.options.commandTimeoutMs,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write binary data passed as a base64 string, which is much smaller on the IPC
+   * bridge than a number array
+   * @param {string} value standard base64
+   * @param {object} options { flush, complete } as for `writeBinary`
+   * @return {Promise<number>}
+   */
+  async writeBase64(
+    value: string,
+    options?: { flush?: boolean; complete?: boolean }
+  ): Promise<number> {
+    try {
+      return await invoke<number>("plugin:serialplugin|write_base64", {
+        value,
+        path: this.options.path,
+        commandTimeoutMs: this.options.commandTimeoutMs,
+        flush: options?.flush,
+        complete: options?.complete,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Write all of `value`, failing once the port accepts no more data for `timeoutMs`
+   * @param {Uint8Array | number[]} value
+   * @param {number} timeoutMs
+   * @return {Promise<number>}
+   */
+  async writeWithTimeout(
+    value: Uint8Array | number[],
+    timeoutMs: number
+  ): Promise<number> {
+    try {
+      return await invoke<number>("plugin:serialplugin|write_with_timeout", {
+        value: Array.from(value),
+        path: this.options.path,
+        timeoutMs,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Get the status of a write queued by `writeAsync`
+   * @param {number} writeId
+   * @return {Promise<WriteStatus>}
+   */
+  async writeStatus(writeId: number): Promise<WriteStatus> {
+    try {
+      return await invoke<WriteStatus>("plugin:serialplugin|write_status", {
+        writeId,
+      });
+    } catch (error) {
+      return Promise.reject(error);
+    }
+  }
+
+  /**
+   * @description: Listen for the completion of writes queued by `writeAsync`
+   * @param {function} fn
+   * @return {Promise<UnlistenFn>}
+   */
+  async listenWriteComplete(
+    fn: (result: WriteResult) => void
+  ): Promise<UnlistenFn> {
+    let sub_path = this.options.path?.toString().replace(/\.+/, "");
+    let writeEvent = `plugin-serialport-write-complete-${sub_path}`;
+    console.log("listen event: " + writeEvent);
+    return await appWindow.listen<PortEvent<WriteResult>>(
+      writeEvent,
+      ({ payload }) => fn(payload.payload)
+    );
+  }
+
 }
 
 export { SerialPort };
@@ -2,15 +2,38 @@
 // SPDX-License-Identifier: Apache-2.0
 // SPDX-License-Identifier: MIT
 
+use crate::compression::Compression;
 use crate::error::Error;
-use crate::state::{ReadData, SerialportInfo, SerialportState};
-use serialport::{DataBits, FlowControl, Parity, SerialPortType, StopBits};
-use std::collections::HashMap;
-use std::io::ErrorKind;
+use crate::events::{
+    emit_event, emit_scan_event, get_event_name, EventSink, AUTO_CLOSED_EVENT, DISCONNECTED_EVENT,
+    EVENT_TYPES, PORT_ADDED_EVENT, PORT_REMOVED_EVENT, READ_EVENT, RECONNECTED_EVENT,
+    RECONNECTING_EVENT, RECONNECT_FAILED_EVENT,
+};
+use crate::framing::LengthPrefix;
+use crate::platform;
+use crate::reader::{spawn_reader, Encoding, ReadMode, ReaderOptions};
+use crate::state::{
+    ChunkSizeHint, ConnectInfo, ControlSignals, DeviceConfig, DisconnectEvent, LastFrame,
+    PingStats, PortInfo, PortList, PortSettings, PortType, ReadTotals, ReconnectAttempt,
+    ReconnectInfo, ReconnectPolicy, RuntimeInfo, SerialportInfo, SerialportState, SignalStep,
+    TrafficChunk, WriteAndRead, WriteStatus,
+};
+use crate::writer::{rs485_transmit, spawn_writer, WriteJob, WriterState};
+use base64::prelude::{Engine, BASE64_STANDARD};
+use log::{debug, error, info, trace, warn};
+use regex::Regex;
+use serialport::{
+    ClearBuffer, DataBits, FlowControl, Parity, SerialPort, SerialPortBuilder, SerialPortType,
+    StopBits,
+};
+use std::collections::{hash_map::Entry, HashMap};
+use std::io::{self, ErrorKind};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc;
-use std::sync::mpsc::{Receiver, Sender, TryRecvError};
-use std::thread;
-use std::time::Duration;
+use std::sync::mpsc::{Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Manager, Runtime, State, Window};
 
 const UNKNOWN: &str = "Unknown";
@@ -18,6 +41,25 @@ const USB: &str = "USB";
 const BLUETOOTH: &str = "Bluetooth";
 const PCI: &str = "PCI";
 
+/// Default limit on the payload a length header may announce in `read`
+const MAX_FRAME_LEN: usize = 64 * 1024;
+
+/// Default limit on a frame waiting for its delimiter in `read`
+const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+
+/// Longest a blocking read waits before checking whether its port is being closed, which bounds
+/// how long `close` takes to interrupt it
+const INTERRUPT_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Default chunk size of the raw read mode and frame size of the fixed read mode
+const READ_SIZE: usize = 1024;
+
+/// Longest `close` waits for the read thread to exit
+const JOIN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Upper bound on the banner read by `open_autoconfig`
+const MAX_BANNER_SIZE: usize = 1024;
+
 /// `get_worksheet` gets the file sheet instance according to `path` and `sheet_name`.
 fn get_serialport<T, F: FnOnce(&mut SerialportInfo) -> Result<T, Error>>(
     state: State<'_, SerialportState>,
@@ -27,9 +69,12 @@ fn get_serialport<T, F: FnOnce(&mut SerialportInfo) -> Result<T, Error>>(
     match state.serialports.lock() {
         Ok(mut map) => match map.get_mut(&path) {
             Some(serialport_info) => f(serialport_info),
-            None => Err(Error::String("serial port not found".to_string())),
+            None => Err(Error::NotOpen(format!(
+                "Serial port {} is not open!",
+                &path
+            ))),
         },
-        Err(error) => Err(Error::String(format!(
+        Err(error) => Err(Error::LockPoisoned(format!(
             "Failed to acquire file lock! {}",
             error
         ))),
@@ -53,6 +98,81 @@ fn get_serialport<T, F: FnOnce(&mut SerialportInfo) -> Result<T, Error>>(
 //     }
 // }
 
+/// `with_serialport` runs the hardware operation `f` on the port at `path`.
+///
+/// With `command_timeout_ms`, `f` runs on its own thread against a clone of the port, so the port
+/// lock is not held, and `Error::TimedOut` is returned if it hasn't finished in time. The clone's
+/// timeout is shortened to the command timeout, so a stuck read or write fails on its own soon
+/// after, but an operation that keeps making progress isn't aborted: a write may still complete,
+/// in part or in full, after `Error::TimedOut` was returned.
+fn with_serialport<T, F>(
+    state: State<'_, SerialportState>,
+    path: String,
+    command_timeout_ms: Option<u64>,
+    f: F,
+) -> Result<T, Error>
+where
+    T: Send + 'static,
+    F: FnOnce(&mut dyn SerialPort) -> Result<T, Error> + Send + 'static,
+{
+    let command_timeout = match command_timeout_ms {
+        Some(command_timeout) => Duration::from_millis(command_timeout),
+        None => {
+            return get_serialport(state, path, |serialport_info| {
+                let result = f(serialport_info.serialport.as_mut());
+                if result.is_ok() {
+                    serialport_info.touch();
+                }
+                result
+            })
+        }
+    };
+    let (mut serial, last_activity) =
+        get_serialport(state, path.clone(), |serialport_info| match serialport_info
+            .serialport
+            .try_clone()
+        {
+            Ok(serial) => Ok((serial, serialport_info.last_activity.clone())),
+            Err(error) => Err(Error::String(format!(
+                "Failed to clone serial port {}: {}",
+                &path, error
+            ))),
+        })?;
+    // On Windows the clone shares the port's timeouts, so the original one is restored afterwards
+    let port_timeout = serial.timeout();
+    if let Err(error) = serial.set_timeout(port_timeout.min(command_timeout)) {
+        return Err(Error::String(format!(
+            "Failed to set timeout of serial port {}: {}",
+            &path, error
+        )));
+    }
+    let (tx, rx) = mpsc::channel();
+    let worker_path = path.clone();
+    thread::spawn(move || {
+        let result = f(serial.as_mut());
+        if let Err(error) = serial.set_timeout(port_timeout) {
+            warn!(
+                "Failed to restore timeout of serial port {}: {}",
+                &worker_path, error
+            );
+        }
+        if result.is_ok() {
+            if let Ok(mut last_activity) = last_activity.lock() {
+                *last_activity = Instant::now();
+            }
+        }
+        // The caller may have given up waiting already
+        let _ = tx.send(result);
+    });
+    match rx.recv_timeout(command_timeout) {
+        Ok(result) => result,
+        Err(_) => Err(Error::TimedOut(format!(
+            "Serial port {} did not complete the command within {:?}",
+            &path, command_timeout
+        ))),
+    }
+}
+
 fn get_data_bits(value: Option<usize>) -> DataBits {
     match value {
         Some(value) => match value {
@@ -66,36 +186,556 @@ fn get_data_bits(value: Option<usize>) -> DataBits {
     }
 }
 
-fn get_flow_control(value: Option<String>) -> FlowControl {
+/// `get_flow_control` parses `value`, ignoring case, and rejects unknown values
+fn get_flow_control(value: Option<String>) -> Result<FlowControl, Error> {
     match value {
-        Some(value) => match value.as_str() {
-            "Software" => FlowControl::Software,
-            "Hardware" => FlowControl::Hardware,
-            _ => FlowControl::None,
+        Some(value) => match value.to_lowercase().as_str() {
+            "none" => Ok(FlowControl::None),
+            "software" => Ok(FlowControl::Software),
+            "hardware" => Ok(FlowControl::Hardware),
+            _ => Err(Error::String(format!(
+                "Invalid flow control {}, expected None, Software or Hardware",
+                value
+            ))),
         },
-        None => FlowControl::None,
+        None => Ok(FlowControl::None),
     }
 }
 
-fn get_parity(value: Option<String>) -> Parity {
+/// `get_parity` parses `value`, ignoring case, and rejects unknown values. Mark and space parity
+/// map to `Parity::None`, as `open_port` and `open_fd` set them up themselves.
+fn get_parity(value: Option<String>) -> Result<Parity, Error> {
     match value {
-        Some(value) => match value.as_str() {
-            "Odd" => Parity::Odd,
-            "Even" => Parity::Even,
-            _ => Parity::None,
+        Some(value) => match value.to_lowercase().as_str() {
+            "none" | "mark" | "space" => Ok(Parity::None),
+            "odd" => Ok(Parity::Odd),
+            "even" => Ok(Parity::Even),
+            _ => Err(Error::String(format!(
+                "Invalid parity {}, expected None, Odd, Even, Mark or Space",
+                value
+            ))),
         },
-        None => Parity::None,
+        None => Ok(Parity::None),
+    }
+}
+
+/// `mark_space` tells whether `parity` names mark parity (`true`) or space parity (`false`)
+fn mark_space(parity: Option<&str>) -> Option<bool> {
+    match parity {
+        Some(parity) if parity.eq_ignore_ascii_case("mark") => Some(true),
+        Some(parity) if parity.eq_ignore_ascii_case("space") => Some(false),
+        _ => None,
+    }
+}
+
+/// `parse_hex` converts a string of hex digit pairs to bytes, ignoring whitespace anywhere in it
+fn parse_hex(value: &str) -> Result<Vec<u8>, Error> {
+    let digits: Vec<char> = value.chars().filter(|c| !c.is_whitespace()).collect();
+    digits
+        .chunks(2)
+        .map(|pair| match pair {
+            [high, low] => match (high.to_digit(16), low.to_digit(16)) {
+                (Some(high), Some(low)) => Ok((high * 16 + low) as u8),
+                _ => Err(Error::String(format!(
+                    "Invalid hex data, {}{} is not a hex byte",
+                    high, low
+                ))),
+            },
+            _ => Err(Error::String(format!(
+                "Invalid hex data, {} digits is not a whole number of bytes",
+                digits.len()
+            ))),
+        })
+        .collect()
+}
+
+/// `with_length_prefix` prepends a header in the `length_prefix` format holding the length of
+/// `value`
+fn with_length_prefix(value: Vec<u8>, length_prefix: Option<String>) -> Result<Vec<u8>, Error> {
+    match length_prefix {
+        Some(length_prefix) => LengthPrefix::parse(&length_prefix)?.prepend(&value),
+        None => Ok(value),
+    }
+}
+
+/// `with_line_ending` appends the terminator named by `line_ending` (`none`, `lf`, `cr` or `crlf`)
+/// to `value`
+fn with_line_ending(mut value: Vec<u8>, line_ending: Option<String>) -> Result<Vec<u8>, Error> {
+    let terminator: &[u8] = match line_ending.as_deref() {
+        None | Some("none") => b"",
+        Some("lf") => b"\n",
+        Some("cr") => b"\r",
+        Some("crlf") => b"\r\n",
+        Some(line_ending) => {
+            return Err(Error::String(format!(
+                "Invalid line ending {}, expected none, lf, cr or crlf",
+                line_ending
+            )))
+        }
+    };
+    value.extend_from_slice(terminator);
+    Ok(value)
+}
+
+/// `open_port` opens the port configured by `builder`. Mark and space `parity`, which `get_parity`
+/// can't express, are set up by the platform where supported. Without `exclusive`, the port is
+/// opened in shared mode where the platform supports it.
+fn open_port(
+    builder: SerialPortBuilder,
+    parity: Option<&str>,
+    exclusive: bool,
+) -> serialport::Result<Box<dyn SerialPort>> {
+    match mark_space(parity) {
+        Some(mark) => platform::open_mark_space(builder, mark, exclusive),
+        None if !exclusive => platform::open_shared(builder),
+        _ => builder.open(),
     }
 }
 
-fn get_stop_bits(value: Option<usize>) -> StopBits {
+/// `get_stop_bits` rejects any number of stop bits but 1 or 2, defaulting to the usual 1
+fn get_stop_bits(value: Option<usize>) -> Result<StopBits, Error> {
     match value {
         Some(value) => match value {
-            1 => StopBits::One,
-            2 => StopBits::Two,
-            _ => StopBits::Two,
+            1 => Ok(StopBits::One),
+            2 => Ok(StopBits::Two),
+            _ => Err(Error::String(format!(
+                "Invalid stop bits {}, expected 1 or 2",
+                value
+            ))),
+        },
+        None => Ok(StopBits::One),
+    }
+}
+
+/// `open_failed` reports that `path` could not be opened, telling a port that doesn't exist or is
+/// held by another process apart from other I/O failures
+fn open_failed(verb: &str, path: &str, error: serialport::Error) -> Error {
+    let message = format!(
+        "Failed to {} {} serial port: {}",
+        verb, path, error.description
+    );
+    let description = error.description.to_lowercase();
+    match error.kind {
+        // `serialport` reports EBUSY as an unknown error and a denied COM port as a missing
+        // device, leaving only their description
+        _ if description.contains("busy") || description.contains("denied") => {
+            Error::PortBusy(message)
+        }
+        serialport::ErrorKind::NoDevice | serialport::ErrorKind::Io(ErrorKind::NotFound) => {
+            Error::PortNotFound(message)
+        }
+        serialport::ErrorKind::Io(ErrorKind::PermissionDenied) => Error::PortBusy(message),
+        serialport::ErrorKind::Io(kind) => Error::Io(io::Error::new(kind, message)),
+        serialport::ErrorKind::InvalidInput => {
+            Error::Io(io::Error::new(ErrorKind::InvalidInput, message))
+        }
+        serialport::ErrorKind::Unknown => Error::Io(io::Error::new(ErrorKind::Other, message)),
+    }
+}
+
+/// `check_not_open` fails with `Error::PortAlreadyOpen` when `path` is open, releasing the lock
+/// right away so the port can be set up without holding it
+fn check_not_open(
+    serialports: &Mutex<HashMap<String, SerialportInfo>>,
+    path: &str,
+) -> Result<(), Error> {
+    match serialports.lock() {
+        Ok(serialports) if serialports.contains_key(path) => Err(Error::PortAlreadyOpen(format!(
+            "Serial port {} is open!",
+            path
+        ))),
+        Ok(_) => Ok(()),
+        Err(error) => Err(Error::LockPoisoned(format!(
+            "Failed to acquire lock: {}",
+            error
+        ))),
+    }
+}
+
+/// `insert_port` registers the set up port `data` as `path`, unless another command opened `path`
+/// in the meantime, in which case `data` and its reader, if any, are stopped, closing it again
+fn insert_port(
+    serialports: &Mutex<HashMap<String, SerialportInfo>>,
+    path: String,
+    mut data: SerialportInfo,
+) -> Result<(), Error> {
+    let error = match serialports.lock() {
+        Ok(mut serialports) => match serialports.entry(path.clone()) {
+            Entry::Occupied(_) => Error::PortAlreadyOpen(format!("Serial port {} is open!", path)),
+            Entry::Vacant(entry) => {
+                entry.insert(data);
+                return Ok(());
+            }
         },
-        None => StopBits::Two,
+        Err(error) => Error::LockPoisoned(format!("Failed to acquire lock: {}", error)),
+    };
+    stop_reader(&mut data, &path);
+    Err(error)
+}
+
+/// `apply_signal_sequence` performs `steps` on `serialport` in order
+fn apply_signal_sequence(
+    serialport: &mut dyn SerialPort,
+    steps: &[SignalStep],
+) -> Result<(), Error> {
+    for step in steps {
+        let result = match step.line.as_str() {
+            "dtr" => serialport.write_data_terminal_ready(step.level),
+            "rts" => serialport.write_request_to_send(step.level),
+            line => {
+                return Err(Error::String(format!(
+                    "Invalid control line {}, expected dtr or rts",
+                    line
+                )))
+            }
+        };
+        if let Err(error) = result {
+            return Err(Error::String(format!(
+                "Failed to set {} to {}: {}",
+                step.line, step.level, error
+            )));
+        }
+        thread::sleep(Duration::from_millis(step.delay_ms));
+    }
+    Ok(())
+}
+
+/// `read_port_settings` reads the current settings of `serialport`
+fn read_port_settings(serialport: &dyn SerialPort) -> Result<PortSettings, Error> {
+    let baud_rate = match serialport.baud_rate() {
+        Ok(baud_rate) => baud_rate,
+        Err(error) => {
+            return Err(Error::String(format!(
+                "Failed to read baud rate: {}",
+                error
+            )))
+        }
+    };
+    let data_bits = match serialport.data_bits() {
+        Ok(DataBits::Five) => 5,
+        Ok(DataBits::Six) => 6,
+        Ok(DataBits::Seven) => 7,
+        Ok(DataBits::Eight) => 8,
+        Err(error) => {
+            return Err(Error::String(format!(
+                "Failed to read data bits: {}",
+                error
+            )))
+        }
+    };
+    let flow_control = match serialport.flow_control() {
+        Ok(FlowControl::None) => None,
+        Ok(FlowControl::Software) => Some("Software".to_string()),
+        Ok(FlowControl::Hardware) => Some("Hardware".to_string()),
+        Err(error) => {
+            return Err(Error::String(format!(
+                "Failed to read flow control: {}",
+                error
+            )))
+        }
+    };
+    let parity = match serialport.parity() {
+        Ok(Parity::None) => None,
+        Ok(Parity::Odd) => Some("Odd".to_string()),
+        Ok(Parity::Even) => Some("Even".to_string()),
+        Err(error) => return Err(Error::String(format!("Failed to read parity: {}", error))),
+    };
+    let stop_bits = match serialport.stop_bits() {
+        Ok(StopBits::One) => 1,
+        Ok(StopBits::Two) => 2,
+        Err(error) => {
+            return Err(Error::String(format!(
+                "Failed to read stop bits: {}",
+                error
+            )))
+        }
+    };
+    Ok(PortSettings {
+        baud_rate,
+        data_bits,
+        flow_control,
+        parity,
+        stop_bits,
+        timeout_ms: serialport.timeout().as_millis() as u64,
+    })
+}
+
+/// `apply_banner_settings` reconfigures `serialport` from the named groups captured in the banner.
+/// Mark and space parity are rejected, as they can only be set up when a port is opened.
+fn apply_banner_settings(
+    serialport: &mut dyn SerialPort,
+    captures: &regex::Captures,
+) -> Result<(), Error> {
+    if let Some(value) = captures.name("baud_rate") {
+        match value.as_str().trim().parse::<u32>() {
+            Ok(baud_rate) => {
+                if let Err(error) = serialport.set_baud_rate(baud_rate) {
+                    return Err(Error::String(format!(
+                        "Failed to set baud rate {}: {}",
+                        baud_rate, error
+                    )));
+                }
+            }
+            Err(_) => {
+                return Err(Error::InvalidInput(format!(
+                    "Invalid baud rate in banner: {}",
+                    value.as_str()
+                )))
+            }
+        }
+    }
+    if let Some(value) = captures.name("data_bits") {
+        let data_bits = match value.as_str().trim().parse::<usize>() {
+            Ok(data_bits @ 5..=8) => get_data_bits(Some(data_bits)),
+            _ => {
+                return Err(Error::InvalidInput(format!(
+                    "Invalid data bits in banner: {}",
+                    value.as_str()
+                )))
+            }
+        };
+        if let Err(error) = serialport.set_data_bits(data_bits) {
+            return Err(Error::String(format!("Failed to set data bits: {}", error)));
+        }
+    }
+    if let Some(value) = captures.name("parity") {
+        // Banners usually use the short form, as in "8N1"
+        let parity = match value.as_str().trim() {
+            "O" | "o" => "Odd",
+            "E" | "e" => "Even",
+            "N" | "n" => "None",
+            parity => parity,
+        };
+        if mark_space(Some(parity)).is_some() {
+            return Err(Error::String(format!(
+                "Parity {} in banner is not supported, mark and space parity can only be set when \
+                 opening the port",
+                parity
+            )));
+        }
+        if let Err(error) = serialport.set_parity(get_parity(Some(parity.to_string()))?) {
+            return Err(Error::String(format!("Failed to set parity: {}", error)));
+        }
+    }
+    if let Some(value) = captures.name("stop_bits") {
+        let stop_bits = match value.as_str().trim().parse::<usize>() {
+            Ok(stop_bits @ 1..=2) => get_stop_bits(Some(stop_bits))?,
+            _ => {
+                return Err(Error::InvalidInput(format!(
+                    "Invalid stop bits in banner: {}",
+                    value.as_str()
+                )))
+            }
+        };
+        if let Err(error) = serialport.set_stop_bits(stop_bits) {
+            return Err(Error::String(format!("Failed to set stop bits: {}", error)));
+        }
+    }
+    if let Some(value) = captures.name("flow_control") {
+        let flow_control = get_flow_control(Some(value.as_str().trim().to_string()))?;
+        if let Err(error) = serialport.set_flow_control(flow_control) {
+            return Err(Error::String(format!(
+                "Failed to set flow control: {}",
+                error
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// A read thread that was signalled to stop, see `take_reader`
+struct StoppedReader {
+    handle: JoinHandle<()>,
+    // How long to wait for it, covering a read that was just started on its clone of the port
+    join_timeout: Duration,
+}
+
+/// `take_reader` signals the read thread of `serialport_info` to stop and returns its handle, so it
+/// can be joined with `join_reader` after the lock on the port map is released
+fn take_reader(serialport_info: &mut SerialportInfo) -> Option<StoppedReader> {
+    if let Some(sender) = serialport_info.sender.take() {
+        // The thread may already have exited on a read error
+        let _ = sender.send(1);
+    }
+    let handle = serialport_info.reader.take()?;
+    let port_timeout = serialport_info.serialport.timeout();
+    let read_timeout = match &serialport_info.reader_options {
+        Some(options) => options.read_timeout(port_timeout),
+        None => port_timeout,
+    };
+    Some(StoppedReader {
+        handle,
+        join_timeout: JOIN_TIMEOUT.max(read_timeout + INTERRUPT_INTERVAL),
+    })
+}
+
+/// `join_reader` waits for a stopped read thread to exit, so its clone of the port is released.
+/// It waits at least `JOIN_TIMEOUT`, and longer for a reader whose read timeout is longer, as the
+/// reader only notices the stop signal between reads. A reader that is still running by then is
+/// left to exit on its own and `Error::TimedOut` is returned.
+fn join_reader(reader: StoppedReader, path: &str) -> Result<(), Error> {
+    let StoppedReader {
+        handle,
+        join_timeout,
+    } = reader;
+    let deadline = Instant::now() + join_timeout;
+    while !handle.is_finished() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(10));
+    }
+    if !handle.is_finished() {
+        return Err(Error::TimedOut(format!(
+            "Read thread of serial port {} did not exit in time",
+            path
+        )));
+    }
+    match handle.join() {
+        Ok(_) => Ok(()),
+        Err(_) => Err(Error::String(format!(
+            "Read thread of serial port {} panicked",
+            path
+        ))),
+    }
+}
+
+/// `stop_reader` stops the read thread of `serialport_info` and waits for it as `join_reader` does.
+/// Only use it on a port that was already removed from the port map.
+fn stop_reader(serialport_info: &mut SerialportInfo, path: &str) {
+    if let Some(reader) = take_reader(serialport_info) {
+        if let Err(error) = join_reader(reader, path) {
+            warn!("{}", error);
+        }
+    }
+}
+
+/// `read_interruptible` reads from `serialport` until some data arrives, `deadline` passes or the
+/// port is closed, which is noticed within `INTERRUPT_INTERVAL`
+fn read_interruptible(
+    serialport: &mut dyn SerialPort,
+    buf: &mut [u8],
+    deadline: Instant,
+    closing: &AtomicBool,
+) -> Result<usize, Error> {
+    loop {
+        if closing.load(Ordering::SeqCst) {
+            return Err(Error::Cancelled("The serial port was closed".to_string()));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(Error::TimedOut("Timed out waiting for data".to_string()));
+        }
+        if let Err(error) = serialport.set_timeout((deadline - now).min(INTERRUPT_INTERVAL)) {
+            return Err(Error::String(format!("Failed to set timeout: {}", error)));
+        }
+        match serialport.read(buf) {
+            Ok(size) => return Ok(size),
+            Err(ref err) if err.kind() == ErrorKind::TimedOut => continue,
+            Err(error) => return Err(Error::String(format!("Failed to read: {}", error))),
+        }
+    }
+}
+
+/// `ping_once` sends `probe` and returns how long it took until the response matched `expect`
+fn ping_once(
+    serialport: &mut dyn SerialPort,
+    probe: &[u8],
+    expect: &regex::bytes::Regex,
+    timeout: Duration,
+    closing: &AtomicBool,
+) -> Result<Duration, Error> {
+    // Drop stale input so an old response can't satisfy the probe
+    if let Err(error) = serialport.clear(ClearBuffer::Input) {
+        return Err(Error::String(format!(
+            "Failed to clear input buffer: {}",
+            error
+        )));
+    }
+    if let Err(error) = serialport.write_all(probe) {
+        return Err(Error::String(format!("Failed to write probe: {}", error)));
+    }
+    let start = Instant::now();
+    wait_for_response(serialport, expect, timeout, closing)?;
+    Ok(start.elapsed())
+}
+
+/// `wait_for_response` reads from `serialport` until the data received matches `expect`, and
+/// returns that data
+fn wait_for_response(
+    serialport: &mut dyn SerialPort,
+    expect: &regex::bytes::Regex,
+    timeout: Duration,
+    closing: &AtomicBool,
+) -> Result<Vec<u8>, Error> {
+    let deadline = Instant::now() + timeout;
+    let mut response: Vec<u8> = Vec::new();
+    let mut buf = [0; 256];
+    loop {
+        let size = match read_interruptible(serialport, &mut buf, deadline, closing) {
+            Ok(size) => size,
+            Err(Error::TimedOut(_)) => {
+                return Err(Error::TimedOut(format!(
+                    "No response matching {} within {:?}",
+                    expect, timeout
+                )))
+            }
+            Err(error) => return Err(error),
+        };
+        response.extend_from_slice(&buf[..size]);
+        if expect.is_match(&response) {
+            return Ok(response);
+        }
+    }
+}
+
+/// `get_port_details` converts `port` to the typed details of `available_ports_detailed`
+fn get_port_details(port: SerialPortType) -> PortInfo {
+    let mut port_info = PortInfo {
+        port_type: PortType::Unknown,
+        vid: None,
+        pid: None,
+        serial_number: None,
+        manufacturer: None,
+        product: None,
+    };
+    match port {
+        SerialPortType::UsbPort(info) => {
+            port_info.port_type = PortType::Usb;
+            port_info.vid = Some(info.vid);
+            port_info.pid = Some(info.pid);
+            port_info.serial_number = info.serial_number;
+            port_info.manufacturer = info.manufacturer;
+            port_info.product = info.product;
+        }
+        SerialPortType::BluetoothPort => port_info.port_type = PortType::Bluetooth,
+        SerialPortType::PciPort => port_info.port_type = PortType::Pci,
+        SerialPortType::Unknown => {}
+    }
+    port_info
+}
+
+/// `read_response` reads from `serialport` one byte at a time until the response ends with
+/// `delimiter` or is `size` bytes long, so nothing after the response is consumed
+fn read_response(
+    serialport: &mut dyn SerialPort,
+    delimiter: Option<&[u8]>,
+    size: Option<usize>,
+    deadline: Instant,
+    closing: &AtomicBool,
+) -> Result<Vec<u8>, Error> {
+    let mut response: Vec<u8> = Vec::new();
+    let mut buf = [0; 1];
+    loop {
+        if read_interruptible(serialport, &mut buf, deadline, closing)? == 0 {
+            continue;
+        }
+        response.push(buf[0]);
+        if let Some(delimiter) = delimiter {
+            if response.ends_with(delimiter) {
+                return Ok(response);
+            }
+        }
+        if size == Some(response.len()) {
+            return Ok(response);
+        }
     }
 }
 
@@ -140,15 +780,115 @@ fn get_port_info(port: SerialPortType) -> HashMap<String, String> {
     port_info
 }
 
-/// `available_ports` get serial port list
-#[tauri::command]
-pub fn available_ports() -> HashMap<String, HashMap<String, String>> {
+/// `spawn_auto_close` closes the port once it has been idle for `after`
+fn spawn_auto_close<R: Runtime>(
+    window: Window<R>,
+    serialports: Arc<Mutex<HashMap<String, SerialportInfo>>>,
+    path: String,
+    last_activity: Arc<Mutex<Instant>>,
+    after: Duration,
+    rx: Receiver<usize>,
+) {
+    thread::spawn(move || loop {
+        let idle = match last_activity.lock() {
+            Ok(last_activity) => last_activity.elapsed(),
+            Err(_) => break,
+        };
+        if idle >= after {
+            match serialports.lock() {
+                Ok(mut map) => {
+                    // Only close the port this timer was started for, not a reopened one
+                    let is_same_port = map
+                        .get(&path)
+                        .is_some_and(|info| Arc::ptr_eq(&info.last_activity, &last_activity));
+                    if !is_same_port {
+                        break;
+                    }
+                    map.remove(&path);
+                }
+                Err(error) => {
+                    error!("Failed to acquire lock: {}", error);
+                    break;
+                }
+            }
+            info!(
+                "Serial port {} auto-closed after {:?} of inactivity",
+                &path, after
+            );
+            emit_event(
+                &window,
+                AUTO_CLOSED_EVENT,
+                &path,
+                format!("Serial port {} auto-closed!", &path),
+            );
+            break;
+        }
+        match rx.recv_timeout(after - idle) {
+            // Timed out, check the idle time again
+            Err(RecvTimeoutError::Timeout) => {}
+            // Port closed or timer cancelled
+            Ok(_) | Err(RecvTimeoutError::Disconnected) => break,
+        }
+    });
+}
+
+/// `enumerate_ports` lists the USB serial ports, or every serial port with `include_all`
+fn enumerate_ports(include_all: bool) -> Vec<serialport::SerialPortInfo> {
     let mut list = match serialport::available_ports() {
         Ok(list) => list,
         Err(_) => vec![],
     };
-    list.retain(|port| matches!(port.port_type, serialport::SerialPortType::UsbPort(_)));
+    if !include_all {
+        list.retain(|port| matches!(port.port_type, serialport::SerialPortType::UsbPort(_)));
+    }
     list.sort_by(|a, b| a.port_name.cmp(&b.port_name));
+    list
+}
+
+/// `list_port_details` enumerates the ports like `list_ports`, with typed details
+fn list_port_details(include_all: bool) -> HashMap<String, PortInfo> {
+    enumerate_ports(include_all)
+        .into_iter()
+        .map(|port| (port.port_name, get_port_details(port.port_type)))
+        .collect()
+}
+
+/// `spawn_port_scan` starts the hot-plug scanner, which enumerates the ports every `interval` and
+/// emits a `port-added` or `port-removed` event for each port that appeared or disappeared since
+/// the previous enumeration, until a stop signal is received on `rx` or the channel is
+/// disconnected
+fn spawn_port_scan<R: Runtime>(
+    window: Window<R>,
+    interval: Duration,
+    include_all: bool,
+    rx: Receiver<usize>,
+) {
+    thread::spawn(move || {
+        let mut known = list_port_details(include_all);
+        // A stop signal or a dropped sender ends the scan, a timeout is the next enumeration
+        while let Err(RecvTimeoutError::Timeout) = rx.recv_timeout(interval) {
+            let current = list_port_details(include_all);
+            for (path, info) in &current {
+                if !known.contains_key(path) {
+                    info!("Serial port {} added", path);
+                    emit_scan_event(&window, PORT_ADDED_EVENT, path, info.clone());
+                }
+            }
+            for (path, info) in known {
+                if !current.contains_key(&path) {
+                    info!("Serial port {} removed", &path);
+                    emit_scan_event(&window, PORT_REMOVED_EVENT, &path, info);
+                }
+            }
+            known = current;
+        }
+        debug!("Port scan stopped");
+    });
+}
+
+/// `list_ports` enumerates the USB serial ports, or every serial port with `include_all`
+fn list_ports(include_all: bool) -> PortList {
+    let list = enumerate_ports(include_all);
 
     let mut result_list: HashMap<String, HashMap<String, String>> = HashMap::new();
 
@@ -156,316 +896,2646 @@ pub fn available_ports() -> HashMap<String, HashMap<String, String>> {
         result_list.insert(p.port_name, get_port_info(p.port_type));
     }
 
-    println!("Serial port list: {:?}", &result_list);
+    debug!("Serial port list: {:?}", &result_list);
 
     result_list
 }
 
-/// `cacel_read` cancel serial port data reading
+/// `available_ports` get serial port list. Only USB ports are listed unless `include_all` is set,
+/// in which case Bluetooth, PCI and unknown ports such as built-in UARTs are listed too.
 #[tauri::command]
-pub async fn cancel_read<R: Runtime>(
+pub fn available_ports(include_all: Option<bool>) -> HashMap<String, HashMap<String, String>> {
+    list_ports(include_all.unwrap_or(false))
+}
+
+/// `available_ports_detailed` get serial port list like `available_ports`, with typed details
+/// that leave missing values out instead of reporting them as `Unknown`
+#[tauri::command]
+pub fn available_ports_detailed(include_all: Option<bool>) -> HashMap<String, PortInfo> {
+    list_port_details(include_all.unwrap_or(false))
+}
+
+/// `available_ports_cached` get serial port list like `available_ports`, reusing the previous
+/// enumeration with the same `include_all` while it is younger than `ttl_ms` (1000 ms by default)
+#[tauri::command]
+pub fn available_ports_cached(
+    state: State<'_, SerialportState>,
+    include_all: Option<bool>,
+    ttl_ms: Option<u64>,
+) -> Result<PortList, Error> {
+    let include_all = include_all.unwrap_or(false);
+    let ttl = Duration::from_millis(ttl_ms.unwrap_or(1000));
+    match state.ports_cache.lock() {
+        Ok(mut cache) => {
+            if let Some((listed_at, ports)) = cache.get(&include_all) {
+                if listed_at.elapsed() < ttl {
+                    return Ok(ports.clone());
+                }
+            }
+            let ports = list_ports(include_all);
+            cache.insert(include_all, (Instant::now(), ports.clone()));
+            Ok(ports)
+        }
+        Err(error) => Err(Error::LockPoisoned(format!(
+            "Failed to acquire lock: {}",
+            error
+        ))),
+    }
+}
+
+/// `port_driver_info` returns whatever platform and driver details are available for a port
+#[tauri::command]
+pub fn port_driver_info(path: String) -> HashMap<String, String> {
+    let mut info: HashMap<String, String> = HashMap::new();
+    info.insert("platform".to_string(), std::env::consts::OS.to_string());
+    // On Windows the product is the device's friendly name
+    if let Ok(ports) = serialport::available_ports() {
+        if let Some(port) = ports.into_iter().find(|port| port.port_name == path) {
+            info.extend(get_port_info(port.port_type));
+        }
+    }
+    platform::driver_info(&path, &mut info);
+    info
+}
+
+/// `get_last_frame` returns the most recent frame emitted by the reader of the specified serial
+/// port, if it was started with `retain_last_frame` and has emitted one since it was started
+#[tauri::command]
+pub fn get_last_frame<R: Runtime>(
     _app: AppHandle<R>,
     _window: Window<R>,
     state: State<'_, SerialportState>,
     path: String,
-) -> Result<(), Error> {
-    get_serialport(state, path.clone(), |serialport_info| {
-        match &serialport_info.sender {
-            Some(sender) => match sender.send(1) {
-                Ok(_) => {}
-                Err(error) => {
-                    return Err(Error::String(format!(
-                        "Failed to cancel serial port data reading: {}",
-                        error
-                    )));
-                }
-            },
-            None => {}
+) -> Result<Option<LastFrame>, Error> {
+    get_serialport(state, path, |serialport_info| {
+        match serialport_info.last_frame.lock() {
+            Ok(last_frame) => Ok(last_frame.clone()),
+            Err(error) => Err(Error::LockPoisoned(format!(
+                "Failed to acquire lock: {}",
+                error
+            ))),
         }
-        serialport_info.sender = None;
-        println!("Cancel {} serial port reading", &path);
-        Ok(())
     })
 }
 
-/// `close` closes the specified serial port
+/// `get_port_config` returns the settings the specified serial port was opened with, as changed by
+/// `set_baud_rate` and `set_timeout` since. Ports opened from a file descriptor have none.
 #[tauri::command]
-pub fn close<R: Runtime>(
+pub fn get_port_config<R: Runtime>(
     _app: AppHandle<R>,
     _window: Window<R>,
     state: State<'_, SerialportState>,
     path: String,
-) -> Result<(), Error> {
-    match state.serialports.lock() {
-        Ok(mut serialports) => {
-            if serialports.remove(&path).is_some() {
-                Ok(())
-            } else {
-                Err(Error::String(format!("Serial port {} is not open!", &path)))
-            }
-        }
-        Err(error) => Err(Error::String(format!("Failed to acquire lock: {}", error))),
-    }
+) -> Result<PortSettings, Error> {
+    get_serialport(
+        state,
+        path.clone(),
+        |serialport_info| match &serialport_info.open_settings {
+            Some(settings) => Ok(settings.clone()),
+            None => Err(Error::String(format!(
+                "Serial port {} was not opened from a path and has no stored configuration",
+                &path
+            ))),
+        },
+    )
 }
 
-/// `close_all` close all serial ports
+/// `get_port_settings` reads the current settings of the specified serial port back from the
+/// driver, using the same values `open` accepts, so changes made since it was opened show up,
+/// unlike with `get_port_config`
 #[tauri::command]
-pub fn close_all<R: Runtime>(
+pub fn get_port_settings<R: Runtime>(
     _app: AppHandle<R>,
     _window: Window<R>,
     state: State<'_, SerialportState>,
-) -> Result<(), Error> {
-    match state.serialports.lock() {
-        Ok(mut map) => {
-            for serialport_info in map.values() {
-                if let Some(sender) = &serialport_info.sender {
-                    match sender.send(1) {
-                        Ok(_) => {}
-                        Err(error) => {
-                            println!("Failed to cancel serial port data reading: {}", error);
-                            return Err(Error::String(format!(
-                                "Failed to cancel serial port data reading: {}",
-                                error
-                            )));
-                        }
-                    }
-                }
-            }
-            print!("Serial ports closed");
-            map.clear();
-            Ok(())
+    path: String,
+) -> Result<PortSettings, Error> {
+    get_serialport(state, path.clone(), |serialport_info| {
+        live_port_settings(serialport_info)
+            .map_err(|error| error.context(&format!("Serial port {}", &path)))
+    })
+}
+
+/// `live_port_settings` reads the settings of an open port back from the driver, keeping the mark
+/// or space parity it was opened with
+fn live_port_settings(serialport_info: &SerialportInfo) -> Result<PortSettings, Error> {
+    let settings = read_port_settings(serialport_info.serialport.as_ref())?;
+    match &serialport_info.open_settings {
+        // termios reports mark and space parity as odd and even
+        Some(open_settings) if mark_space(open_settings.parity.as_deref()).is_some() => {
+            Ok(PortSettings {
+                parity: open_settings.parity.clone(),
+                ..settings
+            })
         }
-        Err(error) => Err(Error::String(format!("Failed to acquire lock: {}", error))),
+        _ => Ok(settings),
     }
 }
 
-/// `force_close` forcibly close the serial port
+/// `get_timeout` returns the read timeout of the specified serial port in milliseconds. Readers
+/// and blocking commands may use a shorter timeout on their own clone of the port, which is not
+/// reflected here.
 #[tauri::command]
-pub fn force_close<R: Runtime>(
+pub fn get_timeout<R: Runtime>(
     _app: AppHandle<R>,
     _window: Window<R>,
     state: State<'_, SerialportState>,
     path: String,
-) -> Result<(), Error> {
-    match state.serialports.lock() {
-        Ok(mut map) => {
-            if let Some(serial) = map.get_mut(&path) {
-                if let Some(sender) = &serial.sender {
-                    match sender.send(1) {
-                        Ok(_) => {}
-                        Err(error) => {
-                            println!("Failed to cancel serial port data reading: {}", error);
-                            return Err(Error::String(format!(
-                                "Failed to cancel serial port data reading: {}",
-                                error
-                            )));
-                        }
-                    }
-                }
-                map.remove(&path);
-                Ok(())
-            } else {
-                Ok(())
-            }
-        }
-        Err(error) => Err(Error::String(format!("Failed to acquire lock: {}", error))),
-    }
+) -> Result<u64, Error> {
+    get_serialport(state, path, |serialport_info| {
+        Ok(serialport_info.serialport.timeout().as_millis() as u64)
+    })
 }
 
-/// `open` opens the specified serial port
+/// `is_open` returns whether the plugin holds the specified serial port open
 #[tauri::command]
-pub fn open<R: Runtime>(
+pub fn is_open<R: Runtime>(
     _app: AppHandle<R>,
-    state: State<'_, SerialportState>,
     _window: Window<R>,
+    state: State<'_, SerialportState>,
     path: String,
-    baud_rate: u32,
-    data_bits: Option<usize>,
-    flow_control: Option<String>,
-    parity: Option<String>,
-    stop_bits: Option<usize>,
-    timeout: Option<u64>,
+) -> Result<bool, Error> {
+    match state.serialports.lock() {
+        Ok(serialports) => Ok(serialports.contains_key(&path)),
+        Err(error) => Err(Error::LockPoisoned(format!(
+            "Failed to acquire lock: {}",
+            error
+        ))),
+    }
+}
+
+/// `list_open_ports` returns the ports the plugin holds open with their current settings, or
+/// with no settings when they can't be read
+#[tauri::command]
+pub fn list_open_ports(
+    state: State<'_, SerialportState>,
+) -> Result<HashMap<String, Option<PortSettings>>, Error> {
+    match state.serialports.lock() {
+        Ok(serialports) => Ok(serialports
+            .iter()
+            .map(|(path, serialport_info)| {
+                let settings = live_port_settings(serialport_info)
+                    .ok()
+                    .or_else(|| serialport_info.open_settings.clone());
+                (path.clone(), settings)
+            })
+            .collect()),
+        Err(error) => Err(Error::LockPoisoned(format!(
+            "Failed to acquire lock: {}",
+            error
+        ))),
+    }
+}
+
+/// `list_events_for_port` returns the names of all events the plugin can emit for `path`
+#[tauri::command]
+pub fn list_events_for_port(path: String) -> Vec<String> {
+    EVENT_TYPES
+        .iter()
+        .map(|event_type| get_event_name(event_type, &path))
+        .collect()
+}
+
+/// `read_available` returns the bytes waiting in the input buffer of the specified serial port,
+/// or nothing if it is empty, without waiting for more. A running reader consumes the input
+/// itself, so this is meant for ports that are polled instead, and fails with
+/// `Error::AlreadyReading` while a reader runs.
+#[tauri::command]
+pub fn read_available<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<Vec<u8>, Error> {
+    get_serialport(state, path.clone(), |serialport_info| {
+        if serialport_info.sender.is_some() {
+            return Err(Error::AlreadyReading(format!(
+                "Serial port {} is reading data, cancel the read first!",
+                &path
+            )));
+        }
+        let available = match serialport_info.serialport.bytes_to_read() {
+            Ok(available) => available as usize,
+            Err(error) => {
+                return Err(Error::String(format!(
+                    "Failed to get the available bytes of serial port {}: {}",
+                    &path, error
+                )))
+            }
+        };
+        if available == 0 {
+            return Ok(Vec::new());
+        }
+        let mut buf = vec![0; available];
+        match serialport_info.serialport.read(&mut buf) {
+            Ok(size) => {
+                buf.truncate(size);
+                serialport_info.touch();
+                Ok(buf)
+            }
+            Err(error) => Err(Error::String(format!(
+                "Failed to read serial port {}: {}",
+                &path, error
+            ))),
+        }
+    })
+}
+
+/// `read_once` waits for data on the specified serial port and returns what a single read
+/// delivers, at most `size` bytes (1024 by default), without starting a reader. It fails with
+/// `Error::TimedOut` when nothing arrives within `timeout` milliseconds, the port's read timeout by
+/// default, and with `Error::Cancelled` when the port is closed meanwhile.
+#[tauri::command(async)]
+pub fn read_once<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    size: Option<usize>,
+    timeout: Option<u64>,
+) -> Result<Vec<u8>, Error> {
+    let size = size.unwrap_or(READ_SIZE);
+    if size == 0 {
+        return Err(Error::String("The read size can not be zero!".to_string()));
+    }
+    // Read from a clone so the port lock is not held and `close` can interrupt the read
+    let (mut serialport, closing, last_activity) =
+        get_serialport(state, path.clone(), |serialport_info| {
+            if serialport_info.sender.is_some() {
+                return Err(Error::AlreadyReading(format!(
+                    "Serial port {} is reading data, cancel the read first!",
+                    &path
+                )));
+            }
+            match serialport_info.serialport.try_clone() {
+                Ok(serialport) => Ok((
+                    serialport,
+                    serialport_info.closing.clone(),
+                    serialport_info.last_activity.clone(),
+                )),
+                Err(error) => Err(Error::String(format!(
+                    "Failed to clone serial port {}: {}",
+                    &path, error
+                ))),
+            }
+        })?;
+    let previous_timeout = serialport.timeout();
+    let deadline = Instant::now()
+        + timeout
+            .map(Duration::from_millis)
+            .unwrap_or(previous_timeout);
+    let mut buf = vec![0; size];
+    let result = read_interruptible(serialport.as_mut(), &mut buf, deadline, &closing);
+    if let Err(error) = serialport.set_timeout(previous_timeout) {
+        warn!("Failed to restore timeout: {}", error);
+    }
+    let count = result?;
+    if let Ok(mut last_activity) = last_activity.lock() {
+        *last_activity = Instant::now();
+    }
+    buf.truncate(count);
+    Ok(buf)
+}
+
+/// `read_control_signals` reads the CTS, DSR, CD and RI input lines of the specified serial port
+#[tauri::command]
+pub fn read_control_signals<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<ControlSignals, Error> {
+    get_serialport(state, path.clone(), |serialport_info| {
+        let serialport = serialport_info.serialport.as_mut();
+        let signals = serialport.read_clear_to_send().and_then(|cts| {
+            Ok(ControlSignals {
+                cts,
+                dsr: serialport.read_data_set_ready()?,
+                carrier_detect: serialport.read_carrier_detect()?,
+                ring_indicator: serialport.read_ring_indicator()?,
+            })
+        });
+        match signals {
+            Ok(signals) => Ok(signals),
+            Err(error) => Err(Error::String(format!(
+                "Failed to read control signals of serial port {}: {}",
+                &path, error
+            ))),
+        }
+    })
+}
+
+/// `read_buffer_len` returns how many bytes the reader has buffered toward its next frame
+#[tauri::command]
+pub fn read_buffer_len<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<usize, Error> {
+    get_serialport(state, path, |serialport_info| {
+        Ok(serialport_info.buffered.load(Ordering::Relaxed))
+    })
+}
+
+/// `read_stats` returns the bytes read, frames emitted and errors reported by the reader of the
+/// specified serial port since `read` was last called on it
+#[tauri::command]
+pub fn read_stats<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<ReadTotals, Error> {
+    get_serialport(state, path, |serialport_info| {
+        Ok(serialport_info.read_counters.totals())
+    })
+}
+
+/// `get_latency_timer` returns the latency timer of an FTDI adapter in milliseconds
+#[tauri::command]
+pub fn get_latency_timer(path: String) -> Result<u8, Error> {
+    platform::latency_timer(&path)
+}
+
+/// `set_latency_timer` sets the latency timer of an FTDI adapter in milliseconds (1-255).
+///
+/// On Linux this writes the `latency_timer` sysfs attribute, which usually requires write access
+/// to it. On Windows the value is stored in the driver's registry parameters, which requires
+/// administrator rights and only takes effect once the adapter is reconnected.
+#[tauri::command]
+pub fn set_latency_timer(path: String, value: u8) -> Result<(), Error> {
+    if value == 0 {
+        return Err(Error::String(
+            "The latency timer must be between 1 and 255 ms".to_string(),
+        ));
+    }
+    platform::set_latency_timer(&path, value)
+}
+
+/// `runtime_info` reports what this build of the plugin supports, so the frontend can pick
+/// between code paths. Tauri always runs async commands on its runtime and every supported
+/// platform has threads, so those two capabilities are always available, while the others depend
+/// on the enabled features and the target platform.
+#[tauri::command]
+pub fn runtime_info() -> RuntimeInfo {
+    RuntimeInfo {
+        version: env!("CARGO_PKG_VERSION"),
+        async_commands: true,
+        background_tasks: true,
+        decompress: cfg!(feature = "decompress"),
+        latency_timer: cfg!(any(target_os = "linux", windows)),
+        mark_space_parity: cfg!(target_os = "linux"),
+    }
+}
+
+/// `recommended_chunk_size` suggests how many bytes to put in a single event.
+///
+/// Event payloads are serialized as JSON number arrays, roughly four bytes of IPC traffic per
+/// data byte, and delivered by evaluating script in the webview. The suggestion is a static
+/// per-platform heuristic:
+///
+/// | platform      | chunk size |
+/// |---------------|------------|
+/// | Windows       | 64 KiB     |
+/// | macOS, Linux  | 16 KiB     |
+/// | Android, iOS  | 8 KiB      |
+#[tauri::command]
+pub fn recommended_chunk_size() -> ChunkSizeHint {
+    let chunk_size = if cfg!(windows) {
+        // WebView2 posts messages without going through script evaluation
+        64 * 1024
+    } else if cfg!(any(target_os = "android", target_os = "ios")) {
+        8 * 1024
+    } else {
+        16 * 1024
+    };
+    ChunkSizeHint {
+        platform: std::env::consts::OS,
+        transport: "events",
+        chunk_size,
+    }
+}
+
+/// `bytes_to_read` returns the number of bytes waiting in the input buffer of the specified
+/// serial port
+#[tauri::command]
+pub fn bytes_to_read<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<u32, Error> {
+    get_serialport(state, path.clone(), |serialport_info| match serialport_info
+        .serialport
+        .bytes_to_read()
+    {
+        Ok(bytes) => Ok(bytes),
+        Err(error) => Err(Error::String(format!(
+            "Failed to get the bytes to read of serial port {}: {}",
+            &path, error
+        ))),
+    })
+}
+
+/// `bytes_to_write` returns the number of bytes waiting in the output buffer of the specified
+/// serial port
+#[tauri::command]
+pub fn bytes_to_write<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<u32, Error> {
+    get_serialport(state, path.clone(), |serialport_info| match serialport_info
+        .serialport
+        .bytes_to_write()
+    {
+        Ok(bytes) => Ok(bytes),
+        Err(error) => Err(Error::String(format!(
+            "Failed to get the bytes to write of serial port {}: {}",
+            &path, error
+        ))),
+    })
+}
+
+/// `cacel_read` cancel serial port data reading, waiting for the read thread to exit as
+/// `join_reader` does, so a new reader doesn't race with it
+#[tauri::command]
+pub async fn cancel_read<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<(), Error> {
+    let reader = get_serialport(state, path.clone(), |serialport_info| {
+        Ok(take_reader(serialport_info))
+    })?;
+    if let Some(reader) = reader {
+        if let Err(error) = join_reader(reader, &path) {
+            warn!("{}", error);
+        }
+    }
+    info!("Cancel {} serial port reading", &path);
+    Ok(())
+}
+
+/// `clear_buffer` discards the bytes waiting in the `input` or `output` buffer of the specified
+/// serial port, or in both with `all`
+#[tauri::command]
+pub fn clear_buffer<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    buffer_type: String,
+) -> Result<(), Error> {
+    let buffer_to_clear = match buffer_type.as_str() {
+        "input" => ClearBuffer::Input,
+        "output" => ClearBuffer::Output,
+        "all" => ClearBuffer::All,
+        buffer_type => {
+            return Err(Error::String(format!(
+                "Invalid buffer type {}, expected input, output or all",
+                buffer_type
+            )))
+        }
+    };
+    get_serialport(state, path.clone(), |serialport_info| match serialport_info
+        .serialport
+        .clear(buffer_to_clear)
+    {
+        Ok(_) => Ok(()),
+        Err(error) => Err(Error::String(format!(
+            "Failed to clear the {} buffer of serial port {}: {}",
+            &buffer_type, &path, error
+        ))),
+    })
+}
+
+/// `close` closes the specified serial port, waiting for its reader to exit as `join_reader` does
+#[tauri::command]
+pub async fn close<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<(), Error> {
+    // Take the port out of the map so the lock isn't held while the reader is joined
+    let removed = match state.serialports.lock() {
+        Ok(mut serialports) => serialports.remove(&path),
+        Err(error) => {
+            return Err(Error::LockPoisoned(format!(
+                "Failed to acquire lock: {}",
+                error
+            )))
+        }
+    };
+    match removed {
+        Some(mut serialport_info) => {
+            stop_reader(&mut serialport_info, &path);
+            Ok(())
+        }
+        None => Err(Error::NotOpen(format!(
+            "Serial port {} is not open!",
+            &path
+        ))),
+    }
+}
+
+/// `close_all` close all serial ports, waiting for each reader to exit as `join_reader` does
+#[tauri::command]
+pub async fn close_all<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+) -> Result<(), Error> {
+    let removed: Vec<(String, SerialportInfo)> = match state.serialports.lock() {
+        Ok(mut map) => map.drain().collect(),
+        Err(error) => {
+            return Err(Error::LockPoisoned(format!(
+                "Failed to acquire lock: {}",
+                error
+            )))
+        }
+    };
+    for (path, mut serialport_info) in removed {
+        stop_reader(&mut serialport_info, &path);
+    }
+    info!("Serial ports closed");
+    Ok(())
+}
+
+/// `flush` waits until the bytes written to the specified serial port have been transmitted, so
+/// closing the port right after doesn't cut them off. With `command_timeout_ms`, `Error::TimedOut`
+/// is returned if they aren't transmitted in time.
+#[tauri::command]
+pub fn flush<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    command_timeout_ms: Option<u64>,
+) -> Result<(), Error> {
+    let port = path.clone();
+    with_serialport(state, path, command_timeout_ms, move |serialport| {
+        drain(serialport, &port)
+    })
+}
+
+/// `force_close` forcibly close the serial port, waiting for its reader to exit as `join_reader`
+/// does
+#[tauri::command]
+pub async fn force_close<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<(), Error> {
+    let removed = match state.serialports.lock() {
+        Ok(mut map) => map.remove(&path),
+        Err(error) => {
+            return Err(Error::LockPoisoned(format!(
+                "Failed to acquire lock: {}",
+                error
+            )))
+        }
+    };
+    if let Some(mut serialport_info) = removed {
+        stop_reader(&mut serialport_info, &path);
+    }
+    Ok(())
+}
+
+/// `force_unlock` tries to make the specified serial port usable again, e.g. after a crash left it
+/// locked. A handle the plugin still holds is dropped after its reader has exited, see
+/// `join_reader`, and the port is opened and closed once to see whether it can be used. Returns
+/// whether that succeeded.
+///
+/// This is best effort: a port held by another process, or by a handle the OS has not released
+/// yet, can't be unlocked this way.
+#[tauri::command(async)]
+pub fn force_unlock<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<bool, Error> {
+    let stale = match state.serialports.lock() {
+        Ok(mut serialports) => serialports.remove(&path),
+        Err(error) => {
+            return Err(Error::LockPoisoned(format!(
+                "Failed to acquire lock: {}",
+                error
+            )))
+        }
+    };
+    if let Some(mut stale) = stale {
+        // The reader's clone of the port would keep it locked for the probe below
+        stop_reader(&mut stale, &path);
+        drop(stale);
+        info!("Dropped the handle of serial port {}", &path);
+    }
+    match serialport::new(path.clone(), 9600).open() {
+        Ok(_) => Ok(true),
+        Err(error) => {
+            warn!(
+                "Serial port {} is still unusable: {}",
+                &path, error.description
+            );
+            Ok(false)
+        }
+    }
+}
+
+/// `open` opens the specified serial port
+///
+/// `parity` is one of `None`, `Odd`, `Even`, `Mark` or `Space`. Mark and space parity are only
+/// supported on Linux. `flow_control` is one of `None`, `Software` or `Hardware`, and `stop_bits`
+/// is 1 or 2. Unknown values are rejected with an error naming the accepted ones, and names are
+/// matched ignoring case. Unless given here or in the plugin defaults, ports use 8 data bits, no
+/// parity, 1 stop bit and no flow control.
+///
+/// With `auto_reconnect`, a port lost while reading is reopened automatically, backing off between
+/// attempts as the `ReconnectPolicy` says. Each attempt emits a `reconnecting` event, success a
+/// `reconnected` event and running out of attempts a `reconnect-failed` event.
+///
+/// `on_open_sequence` lists control line changes, e.g. a DTR/RTS pulse that wakes a modem, that are
+/// performed in order before `open` returns, and again whenever the port is reconnected.
+///
+/// Ports are opened for exclusive access unless `exclusive` is false, which lets other processes
+/// open the port as well on Linux and macOS. It has no effect on Windows. A port held exclusively
+/// by another process fails with `Error::PortBusy`.
+///
+/// With `rs485`, the write commands raise RTS before writing and lower it once the bytes have been
+/// transmitted, which switches RS485 transceivers between sending and receiving. See `set_rs485`.
+#[tauri::command]
+pub fn open<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, SerialportState>,
+    window: Window<R>,
+    path: String,
+    baud_rate: u32,
+    data_bits: Option<usize>,
+    flow_control: Option<String>,
+    parity: Option<String>,
+    stop_bits: Option<usize>,
+    timeout: Option<u64>,
+    auto_close_after_ms: Option<u64>,
+    auto_reconnect: Option<ReconnectPolicy>,
+    on_open_sequence: Option<Vec<SignalStep>>,
+    exclusive: Option<bool>,
+    rs485: Option<bool>,
+) -> Result<(), Error> {
+    debug!("open: {:}", path);
+    // Options passed to this call override the defaults given to the plugin
+    let defaults = &state.defaults;
+    let data_bits = data_bits.or(defaults.data_bits);
+    let flow_control = flow_control.or_else(|| defaults.flow_control.clone());
+    let parity = parity.or_else(|| defaults.parity.clone());
+    let stop_bits = stop_bits.or(defaults.stop_bits);
+    let timeout = timeout.or(defaults.timeout);
+    if let Some(policy) = &auto_reconnect {
+        check_reconnect_policy(policy)?;
+    }
+    check_not_open(&state.serialports, &path)?;
+    // The port is set up without holding the lock, as the open sequence may take a while
+    let serial = match open_port(
+        serialport::new(path.clone(), baud_rate)
+            .data_bits(get_data_bits(data_bits))
+            .flow_control(get_flow_control(flow_control)?)
+            .parity(get_parity(parity.clone())?)
+            .stop_bits(get_stop_bits(stop_bits)?)
+            .timeout(Duration::from_millis(timeout.unwrap_or(200))),
+        parity.as_deref(),
+        exclusive.unwrap_or(true),
+    ) {
+        Ok(serial) => serial,
+        Err(error) => return Err(open_failed("create", &path, error)),
+    };
+    let mut data = SerialportInfo::new(serial);
+    data.exclusive = exclusive.unwrap_or(true);
+    if rs485.unwrap_or(false) {
+        // Release the bus until the first write
+        if let Err(error) = data.serialport.write_request_to_send(false) {
+            return Err(Error::String(format!(
+                "Failed to lower RTS of serial port {}: {}",
+                &path, error
+            )));
+        }
+        data.rs485.store(true, Ordering::SeqCst);
+    }
+    let open_sequence = on_open_sequence.unwrap_or_default();
+    apply_signal_sequence(data.serialport.as_mut(), &open_sequence)?;
+    data.open_sequence = open_sequence;
+    data.open_settings = read_port_settings(data.serialport.as_ref())
+        .ok()
+        .map(|settings| match mark_space(parity.as_deref()) {
+            // termios reports mark and space parity as odd and even
+            Some(_) => PortSettings {
+                parity: parity.clone(),
+                ..settings
+            },
+            None => settings,
+        });
+    data.auto_close_after = auto_close_after_ms.map(Duration::from_millis);
+    data.reconnect_policy = auto_reconnect;
+    if let Some(after) = auto_close_after_ms {
+        let (tx, rx): (Sender<usize>, Receiver<usize>) = mpsc::channel();
+        data.auto_close_sender = Some(tx);
+        spawn_auto_close(
+            window,
+            state.serialports.clone(),
+            path.clone(),
+            data.last_activity.clone(),
+            Duration::from_millis(after),
+            rx,
+        );
+    }
+    insert_port(&state.serialports, path, data)
+}
+
+/// `check_reconnect_policy` rejects a `multiplier` that would shrink the delay between attempts or
+/// can't be computed with
+fn check_reconnect_policy(policy: &ReconnectPolicy) -> Result<(), Error> {
+    match policy.multiplier {
+        Some(multiplier) if !multiplier.is_finite() || multiplier < 1.0 => {
+            Err(Error::String(format!(
+                "Invalid reconnect multiplier {}, expected a finite number of at least 1",
+                multiplier
+            )))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// `step_failed` names the `connect_device` step that failed in its error
+fn step_failed(step: &str, error: Error) -> Error {
+    error.context(&format!("{} failed", step))
+}
+
+/// `connect_device` opens the specified serial port and performs the handshake in `config`. The
+/// port is only registered once every step succeeded; otherwise it is closed again and the error
+/// names the failing step (`open`, `signals`, `init` or `response`).
+#[tauri::command(async)]
+pub fn connect_device<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, SerialportState>,
+    _window: Window<R>,
+    path: String,
+    config: DeviceConfig,
+) -> Result<ConnectInfo, Error> {
+    let expect = match &config.expect {
+        Some(expect) => match regex::bytes::Regex::new(expect) {
+            Ok(expect) => Some(expect),
+            Err(error) => {
+                return Err(Error::String(format!(
+                    "Invalid expect pattern {}: {}",
+                    expect, error
+                )))
+            }
+        },
+        None => None,
+    };
+    let defaults = &state.defaults;
+    let parity = config.parity.or_else(|| defaults.parity.clone());
+    check_not_open(&state.serialports, &path).map_err(|error| step_failed("open", error))?;
+    // The handshake runs without holding the lock, as waiting for the response may take a while
+    let serial = match open_port(
+        serialport::new(path.clone(), config.baud_rate)
+            .data_bits(get_data_bits(config.data_bits.or(defaults.data_bits)))
+            .flow_control(
+                get_flow_control(
+                    config
+                        .flow_control
+                        .or_else(|| defaults.flow_control.clone()),
+                )
+                .map_err(|error| step_failed("open", error))?,
+            )
+            .parity(get_parity(parity.clone()).map_err(|error| step_failed("open", error))?)
+            .stop_bits(
+                get_stop_bits(config.stop_bits.or(defaults.stop_bits))
+                    .map_err(|error| step_failed("open", error))?,
+            )
+            .timeout(Duration::from_millis(
+                config.timeout.or(defaults.timeout).unwrap_or(200),
+            )),
+        parity.as_deref(),
+        true,
+    ) {
+        Ok(serial) => serial,
+        Err(error) => return Err(step_failed("open", open_failed("create", &path, error))),
+    };
+    // Dropping `data` on any of the errors below closes the port again
+    let mut data = SerialportInfo::new(serial);
+    let open_sequence = config.signals.unwrap_or_default();
+    apply_signal_sequence(data.serialport.as_mut(), &open_sequence)
+        .map_err(|error| step_failed("signals", error))?;
+    if let Some(init) = &config.init {
+        if let Err(error) = data.serialport.write_all(init) {
+            return Err(step_failed(
+                "init",
+                Error::String(format!("Failed to write init command: {}", error)),
+            ));
+        }
+    }
+    let response = match &expect {
+        Some(expect) => wait_for_response(
+            data.serialport.as_mut(),
+            expect,
+            Duration::from_millis(config.response_timeout_ms.unwrap_or(1000)),
+            &data.closing,
+        )
+        .map_err(|error| step_failed("response", error))?,
+        None => Vec::new(),
+    };
+
+    let mut settings = read_port_settings(data.serialport.as_ref())?;
+    if mark_space(parity.as_deref()).is_some() {
+        // termios reports mark and space parity as odd and even
+        settings.parity = parity;
+    }
+    data.open_settings = Some(settings.clone());
+    data.open_sequence = open_sequence;
+    data.touch();
+    insert_port(&state.serialports, path.clone(), data)
+        .map_err(|error| step_failed("open", error))?;
+    info!("Serial port {} connected", &path);
+    Ok(ConnectInfo { settings, response })
+}
+
+/// `open_fd` registers an already open file descriptor under `path`, e.g. a PTY created elsewhere.
+///
+/// `fd` must be an open tty. The plugin takes ownership of it and closes it when the port is
+/// closed. The port settings are only applied when `baud_rate` is given. Only available on Unix.
+#[tauri::command]
+pub fn open_fd<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, SerialportState>,
+    _window: Window<R>,
+    path: String,
+    fd: i32,
+    baud_rate: Option<u32>,
+    data_bits: Option<usize>,
+    flow_control: Option<String>,
+    parity: Option<String>,
+    stop_bits: Option<usize>,
+    timeout: Option<u64>,
+) -> Result<(), Error> {
+    #[cfg(unix)]
+    {
+        use serialport::TTYPort;
+        use std::os::fd::FromRawFd;
+
+        // SAFETY: both calls only query the descriptor and fail cleanly on one that isn't open
+        if fd < 0 || unsafe { libc::fcntl(fd, libc::F_GETFD) } == -1 {
+            return Err(Error::String(format!(
+                "Invalid file descriptor {}: {}",
+                fd,
+                io::Error::last_os_error()
+            )));
+        }
+        if unsafe { libc::isatty(fd) } != 1 {
+            return Err(Error::String(format!(
+                "File descriptor {} is not a tty",
+                fd
+            )));
+        }
+
+        let defaults = &state.defaults;
+        match state.serialports.lock() {
+            Ok(mut serialports) => {
+                if serialports.contains_key(&path) {
+                    return Err(Error::PortAlreadyOpen(format!(
+                        "Serial port {} is open!",
+                        path
+                    )));
+                }
+                // SAFETY: the caller hands the descriptor over to the plugin
+                let mut serial = unsafe { TTYPort::from_raw_fd(fd) };
+                let timeout = timeout.or(defaults.timeout).unwrap_or(200);
+                if let Err(error) = serial.set_timeout(Duration::from_millis(timeout)) {
+                    return Err(Error::String(format!("Failed to set timeout: {}", error)));
+                }
+                if let Some(baud_rate) = baud_rate {
+                    let flow_control =
+                        get_flow_control(flow_control.or_else(|| defaults.flow_control.clone()))?;
+                    let parity = parity.or_else(|| defaults.parity.clone());
+                    let mark = mark_space(parity.as_deref());
+                    let parity = get_parity(parity)?;
+                    let stop_bits = get_stop_bits(stop_bits.or(defaults.stop_bits))?;
+                    let result = serial
+                        .set_baud_rate(baud_rate)
+                        .and_then(|_| {
+                            serial.set_data_bits(get_data_bits(data_bits.or(defaults.data_bits)))
+                        })
+                        .and_then(|_| serial.set_flow_control(flow_control))
+                        .and_then(|_| serial.set_parity(parity))
+                        .and_then(|_| serial.set_stop_bits(stop_bits))
+                        .and_then(|_| match mark {
+                            // Set up on top of `Parity::None`, which `get_parity` maps them to
+                            Some(mark) => platform::set_mark_space(&serial, mark),
+                            None => Ok(()),
+                        });
+                    if let Err(error) = result {
+                        return Err(Error::String(format!(
+                            "Failed to configure file descriptor {}: {}",
+                            fd, error
+                        )));
+                    }
+                }
+                debug!("open fd {} as {}", fd, &path);
+                serialports.insert(path, SerialportInfo::new(Box::new(serial)));
+                Ok(())
+            }
+            Err(error) => Err(Error::LockPoisoned(format!(
+                "Failed to acquire lock: {}",
+                error
+            ))),
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (
+            state,
+            path,
+            fd,
+            baud_rate,
+            data_bits,
+            flow_control,
+            parity,
+            stop_bits,
+            timeout,
+        );
+        Err(Error::String(
+            "Opening a file descriptor is only supported on Unix".to_string(),
+        ))
+    }
+}
+
+/// `open_autoconfig` opens the specified serial port at `baud_rate`, reads the device banner and
+/// reconfigures the port from it.
+///
+/// `pattern` is a regular expression matched against the banner. The named capture groups
+/// `baud_rate`, `data_bits`, `parity`, `stop_bits` and `flow_control` are applied when present,
+/// e.g. `(?P<baud_rate>\d+),(?P<data_bits>\d)(?P<parity>[NEO])(?P<stop_bits>\d)`.
+#[tauri::command(async)]
+pub fn open_autoconfig<R: Runtime>(
+    _app: AppHandle<R>,
+    state: State<'_, SerialportState>,
+    _window: Window<R>,
+    path: String,
+    pattern: String,
+    baud_rate: Option<u32>,
+    timeout: Option<u64>,
+    banner_timeout: Option<u64>,
+) -> Result<PortSettings, Error> {
+    let regex = match Regex::new(&pattern) {
+        Ok(regex) => regex,
+        Err(error) => {
+            return Err(Error::String(format!(
+                "Invalid banner pattern {}: {}",
+                pattern, error
+            )))
+        }
+    };
+    check_not_open(&state.serialports, &path)?;
+    // The banner is read without holding the lock, as waiting for it may take a while
+    let mut serial = match serialport::new(path.clone(), baud_rate.unwrap_or(9600))
+        .timeout(Duration::from_millis(
+            timeout.or(state.defaults.timeout).unwrap_or(200),
+        ))
+        .open()
+    {
+        Ok(serial) => serial,
+        Err(error) => return Err(open_failed("create", &path, error)),
+    };
+
+    let deadline = Instant::now() + Duration::from_millis(banner_timeout.unwrap_or(2000));
+    let mut banner: Vec<u8> = Vec::new();
+    let mut buf = [0; 64];
+    while Instant::now() < deadline && banner.len() < MAX_BANNER_SIZE {
+        match serial.read(&mut buf) {
+            Ok(size) => {
+                banner.extend_from_slice(&buf[..size]);
+                if regex.is_match(&String::from_utf8_lossy(&banner)) {
+                    break;
+                }
+            }
+            Err(ref err) if err.kind() == ErrorKind::TimedOut => continue,
+            Err(error) => {
+                return Err(Error::String(format!(
+                    "Failed to read banner from serial port {}: {}",
+                    &path, error
+                )))
+            }
+        }
+    }
+
+    let banner = String::from_utf8_lossy(&banner).to_string();
+    match regex.captures(&banner) {
+        Some(captures) => apply_banner_settings(serial.as_mut(), &captures)?,
+        None => {
+            return Err(Error::String(format!(
+                "Serial port {} banner did not match the pattern: {:?}",
+                &path, banner
+            )))
+        }
+    }
+
+    let settings = read_port_settings(serial.as_ref())?;
+    info!("Serial port {} negotiated settings from banner", &path);
+    let mut data = SerialportInfo::new(serial);
+    data.open_settings = Some(settings.clone());
+    insert_port(&state.serialports, path, data)?;
+    Ok(settings)
+}
+
+/// `ping_device` measures the round-trip latency between writing `probe` and receiving a response
+/// matching the `expect` regular expression, over `samples` attempts.
+///
+/// Closing the port while a ping is in progress aborts it with `Error::Cancelled` within
+/// `INTERRUPT_INTERVAL` (50 ms).
+#[tauri::command(async)]
+pub fn ping_device<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    probe: Vec<u8>,
+    expect: String,
+    timeout_ms: u64,
+    samples: Option<usize>,
+) -> Result<PingStats, Error> {
+    let expect = match regex::bytes::Regex::new(&expect) {
+        Ok(expect) => expect,
+        Err(error) => {
+            return Err(Error::String(format!(
+                "Invalid expect pattern {}: {}",
+                expect, error
+            )))
+        }
+    };
+    let samples = samples.unwrap_or(1).max(1);
+    // Ping on a clone so the port lock is not held and `close` can interrupt the exchange
+    let (mut serialport, closing, last_activity) =
+        get_serialport(state, path.clone(), |serialport_info| {
+            if serialport_info.sender.is_some() {
+                return Err(Error::AlreadyReading(format!(
+                    "Serial port {} is reading data, cancel the read first!",
+                    &path
+                )));
+            }
+            match serialport_info.serialport.try_clone() {
+                Ok(serialport) => Ok((
+                    serialport,
+                    serialport_info.closing.clone(),
+                    serialport_info.last_activity.clone(),
+                )),
+                Err(error) => Err(Error::String(format!(
+                    "Failed to clone serial port {}: {}",
+                    &path, error
+                ))),
+            }
+        })?;
+    let previous_timeout = serialport.timeout();
+    let mut latencies: Vec<u64> = Vec::with_capacity(samples);
+    let mut result = Ok(());
+    for _ in 0..samples {
+        match ping_once(
+            serialport.as_mut(),
+            &probe,
+            &expect,
+            Duration::from_millis(timeout_ms),
+            &closing,
+        ) {
+            Ok(latency) => latencies.push(latency.as_micros() as u64),
+            Err(error) => {
+                result = Err(error);
+                break;
+            }
+        }
+    }
+    if let Err(error) = serialport.set_timeout(previous_timeout) {
+        warn!("Failed to restore timeout: {}", error);
+    }
+    if let Ok(mut last_activity) = last_activity.lock() {
+        *last_activity = Instant::now();
+    }
+    result?;
+
+    Ok(PingStats {
+        samples,
+        min_us: latencies.iter().copied().min().unwrap_or_default(),
+        avg_us: latencies.iter().sum::<u64>() / samples as u64,
+        max_us: latencies.iter().copied().max().unwrap_or_default(),
+    })
+}
+
+/// `start_reader` starts the read thread of `serialport_info` on a clone of its port. With a
+/// reconnect policy, losing the port while reading starts an automatic reconnection.
+fn start_reader<R: Runtime>(
+    window: Window<R>,
+    serialports: &Arc<Mutex<HashMap<String, SerialportInfo>>>,
+    path: &str,
+    serialport_info: &mut SerialportInfo,
+    options: ReaderOptions,
+) -> Result<(), Error> {
+    let on_error: Option<Box<dyn FnOnce() + Send>> = match (
+        &serialport_info.reconnect_policy,
+        reopen_options(serialport_info),
+    ) {
+        (Some(_), Some(mut reopen)) => {
+            // Restart this reader rather than the one that ran before
+            reopen.reader_options = Some(options.clone());
+            let window = window.clone();
+            let serialports = serialports.clone();
+            let path = path.to_string();
+            let last_activity = serialport_info.last_activity.clone();
+            Some(Box::new(move || {
+                spawn_auto_reconnect(window, serialports, path, last_activity, reopen)
+            }))
+        }
+        _ => None,
+    };
+    spawn_port_reader(window, path, serialport_info, options, on_error)
+}
+
+/// `spawn_port_reader` starts the read thread of `serialport_info` on a clone of its port, emitting
+/// its events to `sink`
+fn spawn_port_reader<S: EventSink>(
+    sink: S,
+    path: &str,
+    serialport_info: &mut SerialportInfo,
+    options: ReaderOptions,
+    on_error: Option<Box<dyn FnOnce() + Send>>,
+) -> Result<(), Error> {
+    match serialport_info.serialport.try_clone() {
+        Ok(serial) => {
+            debug!("event: {}", get_event_name(READ_EVENT, path));
+            let (tx, rx): (Sender<usize>, Receiver<usize>) = mpsc::channel();
+            serialport_info.sender = Some(tx);
+            serialport_info.reader_options = Some(options.clone());
+            // `get_last_frame` reports frames of the current reader only
+            if let Ok(mut last_frame) = serialport_info.last_frame.lock() {
+                *last_frame = None;
+            }
+            serialport_info.reader = Some(spawn_reader(
+                sink,
+                path.to_string(),
+                serial,
+                rx,
+                serialport_info,
+                options,
+                on_error,
+            ));
+            Ok(())
+        }
+        Err(error) => {
+            sink.emit_port_event(
+                None,
+                DISCONNECTED_EVENT,
+                path,
+                DisconnectEvent {
+                    path: path.to_string(),
+                    reason: error.to_string(),
+                    kind: "clone",
+                },
+            );
+            Err(Error::String(format!(
+                "Failed to read {} serial port: {}",
+                path, error
+            )))
+        }
+    }
+}
+
+/// What `reopen_port` needs to restore a port as it was
+#[derive(Clone)]
+struct ReopenOptions {
+    settings: PortSettings,
+    auto_close_after: Option<Duration>,
+    // options of the reader to restart, if one was running
+    reader_options: Option<ReaderOptions>,
+    reconnect_policy: Option<ReconnectPolicy>,
+    open_sequence: Vec<SignalStep>,
+    exclusive: bool,
+    rs485: bool,
+}
+
+/// `reopen_options` collects how to reopen `serialport_info`, if it was opened from a path
+fn reopen_options(serialport_info: &SerialportInfo) -> Option<ReopenOptions> {
+    let settings = serialport_info.open_settings.clone()?;
+    let reader_options = match serialport_info.sender {
+        Some(_) => serialport_info.reader_options.clone(),
+        None => None,
+    };
+    Some(ReopenOptions {
+        settings,
+        auto_close_after: serialport_info.auto_close_after,
+        reader_options,
+        reconnect_policy: serialport_info.reconnect_policy.clone(),
+        open_sequence: serialport_info.open_sequence.clone(),
+        exclusive: serialport_info.exclusive,
+        rs485: serialport_info.rs485.load(Ordering::SeqCst),
+    })
+}
+
+/// `reopen_port` opens the port at `path` again, restarting its auto-close timer and reader
+///
+/// It doesn't lock `serialports`, which is only handed to the reader, so it must be called
+/// without holding the lock and its result registered afterwards.
+fn reopen_port<R: Runtime>(
+    window: &Window<R>,
+    serialports: &Arc<Mutex<HashMap<String, SerialportInfo>>>,
+    path: &str,
+    reopen: &ReopenOptions,
+) -> Result<SerialportInfo, Error> {
+    let settings = &reopen.settings;
+    let serial = match open_port(
+        serialport::new(path, settings.baud_rate)
+            .data_bits(get_data_bits(Some(settings.data_bits)))
+            .flow_control(get_flow_control(settings.flow_control.clone())?)
+            .parity(get_parity(settings.parity.clone())?)
+            .stop_bits(get_stop_bits(Some(settings.stop_bits))?)
+            .timeout(Duration::from_millis(settings.timeout_ms)),
+        settings.parity.as_deref(),
+        reopen.exclusive,
+    ) {
+        Ok(serial) => serial,
+        Err(error) => return Err(open_failed("reopen", path, error)),
+    };
+    let mut data = SerialportInfo::new(serial);
+    apply_signal_sequence(data.serialport.as_mut(), &reopen.open_sequence)?;
+    data.open_settings = Some(settings.clone());
+    data.auto_close_after = reopen.auto_close_after;
+    data.reconnect_policy = reopen.reconnect_policy.clone();
+    data.open_sequence = reopen.open_sequence.clone();
+    data.exclusive = reopen.exclusive;
+    data.rs485.store(reopen.rs485, Ordering::SeqCst);
+    if let Some(after) = reopen.auto_close_after {
+        let (tx, rx): (Sender<usize>, Receiver<usize>) = mpsc::channel();
+        data.auto_close_sender = Some(tx);
+        spawn_auto_close(
+            window.clone(),
+            serialports.clone(),
+            path.to_string(),
+            data.last_activity.clone(),
+            after,
+            rx,
+        );
+    }
+    if let Some(options) = &reopen.reader_options {
+        start_reader(
+            window.clone(),
+            serialports,
+            path,
+            &mut data,
+            options.clone(),
+        )?;
+    }
+    Ok(data)
+}
+
+/// `is_same_port` tells whether `path` is still the port whose activity is tracked by
+/// `last_activity`, rather than closed or opened again
+fn is_same_port(
+    serialports: &Mutex<HashMap<String, SerialportInfo>>,
+    path: &str,
+    last_activity: &Arc<Mutex<Instant>>,
+) -> bool {
+    match serialports.lock() {
+        Ok(map) => map
+            .get(path)
+            .is_some_and(|info| Arc::ptr_eq(&info.last_activity, last_activity)),
+        Err(error) => {
+            error!("Failed to acquire lock: {}", error);
+            false
+        }
+    }
+}
+
+/// `spawn_auto_reconnect` starts the thread that tries to reopen the port at `path` after it was
+/// lost, waiting between attempts as its reconnect policy says. The lost port stays registered
+/// meanwhile, and the attempts stop once it has been closed or replaced by another `open`.
+fn spawn_auto_reconnect<R: Runtime>(
+    window: Window<R>,
+    serialports: Arc<Mutex<HashMap<String, SerialportInfo>>>,
+    path: String,
+    last_activity: Arc<Mutex<Instant>>,
+    reopen: ReopenOptions,
+) {
+    let policy = match &reopen.reconnect_policy {
+        Some(policy) => policy.clone(),
+        None => return,
+    };
+    let max_delay = Duration::from_millis(policy.max_ms.unwrap_or(30_000));
+    let multiplier = policy.multiplier.unwrap_or(2.0);
+    thread::spawn(move || {
+        let mut delay = Duration::from_millis(policy.initial_ms.unwrap_or(500)).min(max_delay);
+        let mut attempt = 0;
+        loop {
+            if policy
+                .max_attempts
+                .is_some_and(|max_attempts| attempt >= max_attempts)
+            {
+                emit_event(
+                    &window,
+                    RECONNECT_FAILED_EVENT,
+                    &path,
+                    format!(
+                        "Serial port {} could not be reconnected after {} attempts",
+                        &path, attempt
+                    ),
+                );
+                break;
+            }
+            attempt += 1;
+            emit_event(
+                &window,
+                RECONNECTING_EVENT,
+                &path,
+                ReconnectAttempt {
+                    attempt,
+                    delay_ms: delay.as_millis() as u64,
+                },
+            );
+            thread::sleep(delay);
+
+            // Only reopen the port this reconnection was started for
+            if !is_same_port(&serialports, &path, &last_activity) {
+                break;
+            }
+            // The port is reopened without holding the lock, as the open sequence may take a while
+            let result = reopen_port(&window, &serialports, &path, &reopen);
+            let result = match (result, serialports.lock()) {
+                (Ok(mut data), Ok(mut map)) => {
+                    // The port may have been closed or opened again meanwhile
+                    if !map
+                        .get(&path)
+                        .is_some_and(|info| Arc::ptr_eq(&info.last_activity, &last_activity))
+                    {
+                        drop(map);
+                        stop_reader(&mut data, &path);
+                        break;
+                    }
+                    map.insert(path.clone(), data);
+                    Ok(())
+                }
+                (Err(error), _) => Err(error),
+                (_, Err(error)) => {
+                    error!("Failed to acquire lock: {}", error);
+                    break;
+                }
+            };
+            match result {
+                Ok(_) => {
+                    info!(
+                        "Serial port {} reconnected after {} attempts",
+                        &path, attempt
+                    );
+                    emit_event(
+                        &window,
+                        RECONNECTED_EVENT,
+                        &path,
+                        ReconnectInfo {
+                            path: path.clone(),
+                            settings: reopen.settings.clone(),
+                        },
+                    );
+                    break;
+                }
+                Err(error) => warn!("{}", error),
+            }
+            // `check_reconnect_policy` only admits finite multipliers, but the product may still
+            // be too large for a `Duration`
+            delay = Duration::try_from_secs_f64(delay.as_secs_f64() * multiplier)
+                .unwrap_or(max_delay)
+                .min(max_delay);
+        }
+    });
+}
+
+/// `read` read the specified serial port
+///
+/// Frames end at `delimiter` (a newline by default), which `include_delimiter` can strip from
+/// them. `read_mode` selects other framing: `raw` emits every chunk of up to `size` bytes as it's
+/// read and `fixed` emits frames of exactly `size` bytes (1024 by default). Without `read_mode`, a
+/// `size` selects `fixed` unless a `delimiter` or `length_prefix` is also given.
+/// `timeout` overrides the read timeout the port was opened with for this reader only. It only
+/// bounds how long each read on the reader's clone of the port blocks, not when frames are emitted.
+/// When `max_latency_ms` is set, a partial frame is also emitted once its first byte has been
+/// buffered for that long. When `stats_interval_ms` is set, a `stats` event with the bytes and
+/// frames read during each interval is emitted.
+/// `encoding` selects how frames are emitted in `ReadData`'s `data`: `bytes`, an array of byte
+/// values, by default, `hex`, a string of two lowercase hex digits per byte, or `base64`, a
+/// standard base64 string that keeps large binary frames small on the IPC bridge.
+/// `hexdump` adds a printable offset/hex/ascii dump of each frame next to the raw bytes.
+/// `target_label` delivers the events only to the webview window with that label.
+/// `detect_break` emits a `break` event when a break condition is seen on the line.
+/// `decompress` (`gzip`, `zlib` or `deflate`) decompresses each frame before it is emitted and
+/// reports frames that fail to decompress, or decompress to more than `max_message_size` bytes,
+/// with an `error` event. It requires the `decompress` feature.
+/// `retain_last_frame` keeps a copy of the most recent frame for `get_last_frame`.
+/// `strip_bytes` lists byte values, e.g. line noise, that are dropped before framing.
+/// `length_prefix` (`u8`, `u16be`, `u16le`, `u32be` or `u32le`) splits frames by a length header
+/// instead of `delimiter` and emits each payload without its header. Headers announcing more than
+/// `max_frame_len` bytes (64 KiB by default) are reported with an `error` event and dropped.
+/// A frame that grows to `max_message_size` bytes (1 MiB by default) without a delimiter is emitted
+/// flagged as `truncated`.
+/// `sequence_offset` and `sequence_length` locate a big-endian sequence number (1-8 bytes) in each
+/// frame, and a `sequence-gap` event is emitted when a frame's number doesn't follow the previous.
+#[tauri::command]
+pub fn read<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    timeout: Option<u64>,
+    size: Option<usize>,
+    delimiter: Option<Vec<u8>>,
+    include_delimiter: Option<bool>,
+    max_latency_ms: Option<u64>,
+    stats_interval_ms: Option<u64>,
+    hexdump: Option<bool>,
+    target_label: Option<String>,
+    detect_break: Option<bool>,
+    sequence_offset: Option<usize>,
+    sequence_length: Option<usize>,
+    length_prefix: Option<String>,
+    max_frame_len: Option<usize>,
+    max_message_size: Option<usize>,
+    strip_bytes: Option<Vec<u8>>,
+    retain_last_frame: Option<bool>,
+    decompress: Option<String>,
+    read_mode: Option<String>,
+    encoding: Option<String>,
+) -> Result<(), Error> {
+    if let Some(label) = &target_label {
+        if app.get_webview_window(label).is_none() {
+            return Err(Error::String(format!("Window {} not found", label)));
+        }
+    }
+    let length_prefix = match length_prefix {
+        Some(length_prefix) => Some(LengthPrefix::parse(&length_prefix)?),
+        None => None,
+    };
+    // A size without a delimiter or length header asks for fixed-size frames
+    let read_mode = match (read_mode, size, &delimiter, &length_prefix) {
+        (Some(read_mode), _, _, _) => Some(read_mode),
+        (None, Some(_), None, None) => Some("fixed".to_string()),
+        _ => None,
+    };
+    let mode = match read_mode {
+        Some(read_mode) => ReadMode::parse(&read_mode, size.unwrap_or(READ_SIZE))?,
+        None => ReadMode::Line,
+    };
+    let delimiter = delimiter.unwrap_or_else(|| vec![b'\n']);
+    if delimiter.is_empty() {
+        return Err(Error::String("The delimiter can not be empty!".to_string()));
+    }
+    if mode != ReadMode::Line && length_prefix.is_some() {
+        return Err(Error::String(
+            "A length prefix can only be used in line mode".to_string(),
+        ));
+    }
+    let decompress = match decompress {
+        Some(decompress) => Some(Compression::parse(&decompress)?),
+        None => None,
+    };
+    let encoding = match encoding {
+        Some(encoding) => Encoding::parse(&encoding)?,
+        None => Encoding::Bytes,
+    };
+    let sequence_field = match sequence_offset {
+        Some(offset) => match sequence_length.unwrap_or(1) {
+            length @ 1..=8 => Some((offset, length)),
+            length => {
+                return Err(Error::String(format!(
+                    "Invalid sequence length {}, expected 1 to 8 bytes",
+                    length
+                )))
+            }
+        },
+        None => None,
+    };
+    get_serialport(state.clone(), path.clone(), |serialport_info| {
+        if serialport_info.sender.is_some() {
+            debug!("Serial port {} is already reading data!", &path);
+            Ok(())
+        } else {
+            info!("Serial port {} starts reading data!", &path);
+            serialport_info.read_counters.reset();
+            start_reader(
+                window,
+                &state.serialports,
+                &path,
+                serialport_info,
+                ReaderOptions {
+                    mode,
+                    delimiter,
+                    include_delimiter: include_delimiter.unwrap_or(true),
+                    length_prefix,
+                    max_frame_len: max_frame_len.unwrap_or(MAX_FRAME_LEN),
+                    max_message_size: max_message_size.unwrap_or(MAX_MESSAGE_SIZE).max(1),
+                    timeout: timeout.map(Duration::from_millis),
+                    max_latency: max_latency_ms.map(Duration::from_millis),
+                    stats_interval: stats_interval_ms.map(Duration::from_millis),
+                    encoding,
+                    hexdump: hexdump.unwrap_or(false),
+                    target_label,
+                    detect_break: detect_break.unwrap_or(false),
+                    decompress,
+                    retain_last_frame: retain_last_frame.unwrap_or(false),
+                    strip_bytes: strip_bytes.unwrap_or_default(),
+                    sequence_field,
+                },
+            )
+        }
+    })
+}
+
+/// `reconnect` closes the port at `path` and opens it again with the settings it was opened with,
+/// e.g. after the device was unplugged and plugged back in. The auto-close timer and a reader
+/// that was running are restarted with their original options.
+#[tauri::command(async)]
+pub fn reconnect<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<ReconnectInfo, Error> {
+    let (mut stale, reopen) = match state.serialports.lock() {
+        Ok(mut serialports) => {
+            let stale = match serialports.remove(&path) {
+                Some(stale) => stale,
+                None => {
+                    return Err(Error::NotOpen(format!(
+                        "Serial port {} is not open!",
+                        &path
+                    )))
+                }
+            };
+            match reopen_options(&stale) {
+                Some(reopen) => (stale, reopen),
+                None => {
+                    serialports.insert(path.clone(), stale);
+                    return Err(Error::String(format!(
+                        "Serial port {} was not opened from a path and can not be reconnected",
+                        &path
+                    )));
+                }
+            }
+        }
+        Err(error) => {
+            return Err(Error::LockPoisoned(format!(
+                "Failed to acquire lock: {}",
+                error
+            )))
+        }
+    };
+    // The reader's clone has to be released before the port can be opened again on Windows
+    stop_reader(&mut stale, &path);
+    // Dropping the stale handle also stops its auto-close timer
+    drop(stale);
+
+    // The port is reopened without holding the lock, as the open sequence may take a while
+    let data = reopen_port(&window, &state.serialports, &path, &reopen)?;
+    insert_port(&state.serialports, path.clone(), data)?;
+    info!("Serial port {} reconnected", &path);
+    Ok(ReconnectInfo {
+        path,
+        settings: reopen.settings,
+    })
+}
+
+/// `run_blocking` runs `task` on the blocking thread pool, so an async command can wait on the
+/// port without stalling the async runtime
+async fn run_blocking<T, F>(task: F) -> Result<T, Error>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, Error> + Send + 'static,
+{
+    match tauri::async_runtime::spawn_blocking(task).await {
+        Ok(result) => result,
+        Err(error) => Err(Error::String(format!("Blocking task failed: {}", error))),
+    }
+}
+
+/// `record_traffic` captures the raw bytes received on the specified serial port for `duration_ms`
+/// and returns them with the time each chunk arrived. A running reader is paused during the
+/// recording, so its events resume afterwards without the recorded bytes.
+#[tauri::command]
+pub async fn record_traffic<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    duration_ms: u64,
+) -> Result<Vec<TrafficChunk>, Error> {
+    let (mut serial, closing, reader_options, reader) =
+        get_serialport(state.clone(), path.clone(), |serialport_info| {
+            let serial = match serialport_info.serialport.try_clone() {
+                Ok(serial) => serial,
+                Err(error) => {
+                    return Err(Error::String(format!(
+                        "Failed to clone serial port {}: {}",
+                        &path, error
+                    )))
+                }
+            };
+            let reader_options = match serialport_info.sender {
+                Some(_) => serialport_info.reader_options.clone(),
+                None => None,
+            };
+            let reader = take_reader(serialport_info);
+            Ok((
+                serial,
+                serialport_info.closing.clone(),
+                reader_options,
+                reader,
+            ))
+        })?;
+    // Joined without holding the lock, so other commands aren't blocked meanwhile
+    let reader_path = path.clone();
+    let capture = run_blocking(move || {
+        if let Some(reader) = reader {
+            if let Err(error) = join_reader(reader, &reader_path) {
+                warn!("{}", error);
+            }
+        }
+
+        let start = Instant::now();
+        let deadline = start + Duration::from_millis(duration_ms);
+        let mut capture = Vec::new();
+        let mut buf = [0; 1024];
+        loop {
+            match read_interruptible(serial.as_mut(), &mut buf, deadline, &closing) {
+                Ok(size) => capture.push(TrafficChunk {
+                    offset_ms: start.elapsed().as_millis() as u64,
+                    data: buf[..size].to_vec(),
+                }),
+                Err(Error::TimedOut(_)) => break,
+                Err(error) => return Err(error),
+            }
+        }
+        Ok(capture)
+    })
+    .await?;
+    info!(
+        "Recorded {} chunks from serial port {}",
+        capture.len(),
+        &path
+    );
+
+    if let Some(options) = reader_options {
+        let serialports = state.serialports.clone();
+        get_serialport(state, path.clone(), |serialport_info| {
+            start_reader(window, &serialports, &path, serialport_info, options)
+        })?;
+    }
+    Ok(capture)
+}
+
+/// `set_break_state` asserts or clears a break condition on the specified serial port
+fn set_break_state(
+    state: State<'_, SerialportState>,
+    path: String,
+    asserted: bool,
+) -> Result<(), Error> {
+    get_serialport(state, path.clone(), |serialport_info| {
+        let result = if asserted {
+            serialport_info.serialport.set_break()
+        } else {
+            serialport_info.serialport.clear_break()
+        };
+        match result {
+            Ok(_) => Ok(()),
+            Err(error) => Err(Error::String(format!(
+                "Failed to {} break on serial port {}: {}",
+                if asserted { "set" } else { "clear" },
+                &path,
+                error
+            ))),
+        }
+    })
+}
+
+/// `send_break` transmits a break condition on the specified serial port for `duration_ms`
+/// (250 ms by default). The port isn't locked in between, so other commands keep working.
+#[tauri::command]
+pub async fn send_break<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    duration_ms: Option<u64>,
+) -> Result<(), Error> {
+    set_break_state(state.clone(), path.clone(), true)?;
+    let duration = Duration::from_millis(duration_ms.unwrap_or(250));
+    let slept = run_blocking(move || {
+        thread::sleep(duration);
+        Ok(())
+    })
+    .await;
+    // The break is cleared even if the wait failed, so the line isn't left held
+    set_break_state(state, path, false)?;
+    slept
+}
+
+/// `set_break` starts a break condition on the specified serial port, held until `clear_break`
+#[tauri::command]
+pub fn set_break<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<(), Error> {
+    set_break_state(state, path, true)
+}
+
+/// `clear_break` ends a break condition started by `set_break`
+#[tauri::command]
+pub fn clear_break<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<(), Error> {
+    set_break_state(state, path, false)
+}
+
+/// `set_baud_rate` changes the baud rate of the specified serial port while it stays open, keeping
+/// its reader and control line levels, and returns the previous baud rate. A later `reconnect`
+/// uses the new baud rate.
+#[tauri::command]
+pub fn set_baud_rate<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    baud_rate: u32,
+) -> Result<u32, Error> {
+    get_serialport(state, path.clone(), |serialport_info| {
+        let previous = match serialport_info.serialport.baud_rate() {
+            Ok(previous) => previous,
+            Err(error) => {
+                return Err(Error::String(format!(
+                    "Failed to get the baud rate of serial port {}: {}",
+                    &path, error
+                )))
+            }
+        };
+        match serialport_info.serialport.set_baud_rate(baud_rate) {
+            Ok(_) => {
+                if let Some(settings) = &mut serialport_info.open_settings {
+                    settings.baud_rate = baud_rate;
+                }
+                Ok(previous)
+            }
+            Err(error) => Err(Error::String(format!(
+                "Failed to set the baud rate of serial port {}: {}",
+                &path, error
+            ))),
+        }
+    })
+}
+
+/// `set_read_target` changes where the reader of the specified serial port emits its events, taking
+/// effect with the next event: `global` emits to all targets, `caller` to the calling window and
+/// anything else to the webview window with that label.
+#[tauri::command]
+pub fn set_read_target<R: Runtime>(
+    app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    target: String,
+) -> Result<(), Error> {
+    let target_label = match target.as_str() {
+        "global" => None,
+        "caller" => Some(window.label().to_string()),
+        label => {
+            if app.get_webview_window(label).is_none() {
+                return Err(Error::String(format!("Window {} not found", label)));
+            }
+            Some(label.to_string())
+        }
+    };
+    get_serialport(state, path, |serialport_info| {
+        // Keep the target when the reader is restarted by a reconnection
+        if let Some(options) = &mut serialport_info.reader_options {
+            options.target_label = target_label.clone();
+        }
+        match serialport_info.read_target.lock() {
+            Ok(mut read_target) => {
+                *read_target = target_label;
+                Ok(())
+            }
+            Err(error) => Err(Error::LockPoisoned(format!(
+                "Failed to acquire lock: {}",
+                error
+            ))),
+        }
+    })
+}
+
+/// `set_rs485` turns RS485 direction control of the specified serial port on or off. While it's on,
+/// the write commands raise RTS, write, wait until the bytes have been transmitted and lower RTS
+/// again, so a half-duplex transceiver whose driver enable is wired to RTS only drives the bus
+/// while sending. RTS is lowered right away when turning it on.
+#[tauri::command]
+pub fn set_rs485<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    enabled: bool,
 ) -> Result<(), Error> {
-    println!("open: {:}", path);
-    match state.serialports.lock() {
-        Ok(mut serialports) => {
-            if serialports.contains_key(&path) {
-                return Err(Error::String(format!("Serial port {} is open!", path)));
-            }
-            match serialport::new(path.clone(), baud_rate)
-                .data_bits(get_data_bits(data_bits))
-                .flow_control(get_flow_control(flow_control))
-                .parity(get_parity(parity))
-                .stop_bits(get_stop_bits(stop_bits))
-                .timeout(Duration::from_millis(timeout.unwrap_or(200)))
-                .open()
-            {
-                Ok(serial) => {
-                    let data = SerialportInfo {
-                        serialport: serial,
-                        sender: None,
-                    };
-                    serialports.insert(path, data);
-                    Ok(())
-                }
-                Err(error) => Err(Error::String(format!(
-                    "Failed to create {} serial port: {}",
-                    path, error.description
-                ))),
+    get_serialport(state, path.clone(), |serialport_info| {
+        if enabled {
+            if let Err(error) = serialport_info.serialport.write_request_to_send(false) {
+                return Err(Error::String(format!(
+                    "Failed to lower RTS of serial port {}: {}",
+                    &path, error
+                )));
             }
         }
-        Err(error) => Err(Error::String(format!("Failed to acquire lock: {}", error))),
-    }
+        serialport_info.rs485.store(enabled, Ordering::SeqCst);
+        Ok(())
+    })
 }
 
-/// `read` read the specified serial port
+/// `set_timeout` changes the read timeout of the specified serial port to `timeout_ms`. A running
+/// reader keeps the timeout it was started with. A later `reconnect` uses the new timeout.
 #[tauri::command]
-pub fn read<R: Runtime>(
+pub fn set_timeout<R: Runtime>(
     _app: AppHandle<R>,
-    window: Window<R>,
+    _window: Window<R>,
     state: State<'_, SerialportState>,
     path: String,
-    timeout: Option<u64>,
-    size: Option<usize>,
+    timeout_ms: u64,
 ) -> Result<(), Error> {
-    let event_path = path.replace(".", "");
-    let disconnected_event = format!("plugin-serialport-disconnected-{}", &event_path);
-    get_serialport(state.clone(), path.clone(), |serialport_info| {
-        if serialport_info.sender.is_some() {
-            println!("Serial port {} is already reading data!", &path);
+    get_serialport(state, path.clone(), |serialport_info| match serialport_info
+        .serialport
+        .set_timeout(Duration::from_millis(timeout_ms))
+    {
+        Ok(_) => {
+            if let Some(settings) = &mut serialport_info.open_settings {
+                settings.timeout_ms = timeout_ms;
+            }
+            Ok(())
+        }
+        Err(error) => Err(Error::String(format!(
+            "Failed to set the timeout of serial port {}: {}",
+            &path, error
+        ))),
+    })
+}
+
+/// `start_port_scan` watches for ports being plugged in and out, enumerating them every
+/// `interval_ms` (1000 ms by default) and emitting `plugin-serialport-port-added` and
+/// `plugin-serialport-port-removed` events with the port's details. Ports present when the scan
+/// starts are not reported. Only USB ports are watched unless `include_all` is set. A scan that is
+/// already running is replaced.
+#[tauri::command]
+pub fn start_port_scan<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    interval_ms: Option<u64>,
+    include_all: Option<bool>,
+) -> Result<(), Error> {
+    match state.port_scan.lock() {
+        Ok(mut port_scan) => {
+            let (tx, rx): (Sender<usize>, Receiver<usize>) = mpsc::channel();
+            // Replacing the sender disconnects the channel of a running scan, which stops it
+            *port_scan = Some(tx);
+            spawn_port_scan(
+                window,
+                Duration::from_millis(interval_ms.unwrap_or(1000).max(1)),
+                include_all.unwrap_or(false),
+                rx,
+            );
+            Ok(())
+        }
+        Err(error) => Err(Error::LockPoisoned(format!(
+            "Failed to acquire lock: {}",
+            error
+        ))),
+    }
+}
+
+/// `stop_port_scan` stops the scan started by `start_port_scan`, if any
+#[tauri::command]
+pub fn stop_port_scan<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+) -> Result<(), Error> {
+    match state.port_scan.lock() {
+        Ok(mut port_scan) => {
+            if let Some(sender) = port_scan.take() {
+                // The scanner may be between enumerations or already gone
+                let _ = sender.send(1);
+            }
             Ok(())
+        }
+        Err(error) => Err(Error::LockPoisoned(format!(
+            "Failed to acquire lock: {}",
+            error
+        ))),
+    }
+}
+
+/// `stop_and_join_reader` stops the read thread and waits until it has exited, so the reader's
+/// clone of the port is dropped when this returns. A reader that doesn't exit in time, see
+/// `join_reader`, fails it with `Error::TimedOut`.
+#[tauri::command]
+pub async fn stop_and_join_reader<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+) -> Result<(), Error> {
+    // Joined without holding the lock, so other commands aren't blocked meanwhile
+    let reader = get_serialport(state, path.clone(), |serialport_info| {
+        Ok(take_reader(serialport_info))
+    })?;
+    if let Some(reader) = reader {
+        join_reader(reader, &path)?;
+        debug!("Serial port {} reader joined", &path);
+    }
+    Ok(())
+}
+
+/// `rs485_flag` returns the RS485 setting of the specified serial port, see `set_rs485`
+fn rs485_flag(state: State<'_, SerialportState>, path: &str) -> Result<Arc<AtomicBool>, Error> {
+    get_serialport(state, path.to_string(), |serialport_info| {
+        Ok(serialport_info.rs485.clone())
+    })
+}
+
+/// `drain` waits until the bytes written to `serialport` have been transmitted
+fn drain(serialport: &mut dyn SerialPort, path: &str) -> Result<(), Error> {
+    match serialport.flush() {
+        Ok(_) => Ok(()),
+        Err(error) => Err(Error::String(format!(
+            "Failed to flush serial port {}: {}",
+            path, error
+        ))),
+    }
+}
+
+/// `write_fully` writes all of `data`, returning how much was written when it fails part way
+fn write_fully(serialport: &mut dyn SerialPort, data: &[u8]) -> Result<usize, (usize, io::Error)> {
+    let mut written = 0;
+    while written < data.len() {
+        match serialport.write(&data[written..]) {
+            Ok(0) => return Err((written, io::Error::from(ErrorKind::WriteZero))),
+            Ok(size) => written += size,
+            Err(ref error) if error.kind() == ErrorKind::Interrupted => {}
+            Err(error) => return Err((written, error)),
+        }
+    }
+    Ok(written)
+}
+
+/// `write_data` writes `data` for `write` and `write_binary`, emitting a `disconnected` event when
+/// the port fails
+fn write_data<R: Runtime>(
+    window: &Window<R>,
+    path: &str,
+    serialport: &mut dyn SerialPort,
+    data: &[u8],
+    flush: bool,
+    complete: bool,
+    rs485: bool,
+) -> Result<usize, Error> {
+    rs485_transmit(serialport, path, rs485, |serialport| {
+        let result = if complete {
+            write_fully(serialport, data)
         } else {
-            println!("Serial port {} starts reading data!", &path);
+            serialport.write(data).map_err(|error| (0, error))
+        };
+        match result {
+            Ok(size) => {
+                if flush {
+                    drain(serialport, path)?;
+                }
+                Ok(size)
+            }
+            // A non-blocking port that can't take more data stalls like a timed out one
+            Err((written, error))
+                if matches!(error.kind(), ErrorKind::TimedOut | ErrorKind::WouldBlock) =>
+            {
+                Err(Error::TimedOut(format!(
+                    "Timed out writing to serial port {} after {} of {} bytes",
+                    path,
+                    written,
+                    data.len()
+                )))
+            }
+            Err((_, error)) => {
+                emit_event(
+                    window,
+                    DISCONNECTED_EVENT,
+                    path,
+                    DisconnectEvent {
+                        path: path.to_string(),
+                        reason: error.to_string(),
+                        kind: "write",
+                    },
+                );
+                Err(Error::String(format!(
+                    "Failed to write data to serial port {}: {}",
+                    path, error
+                )))
+            }
+        }
+    })
+}
+
+/// `write` writes to the specified serial port
+///
+/// With `command_timeout_ms`, `Error::TimedOut` is returned if the write doesn't complete in time.
+/// With `length_prefix` (`u8`, `u16be`, `u16le`, `u32be` or `u32le`), `value` is preceded by a
+/// header holding its length, and the returned size includes the header.
+/// With `flush`, it only returns once the written bytes have left the output buffer.
+/// With `complete`, writing continues until all of `value` is written instead of returning after
+/// the first partial write. A write timing out part way fails with `Error::TimedOut`, whose message
+/// tells how many bytes were written.
+/// `line_ending` (`none`, `lf`, `cr` or `crlf`) is appended to `value`, inside any length prefix,
+/// and is included in the returned size.
+#[tauri::command]
+pub fn write<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    value: String,
+    command_timeout_ms: Option<u64>,
+    length_prefix: Option<String>,
+    flush: Option<bool>,
+    line_ending: Option<String>,
+    complete: Option<bool>,
+) -> Result<usize, Error> {
+    // The payload may be sensitive, so it is only logged at the trace level
+    debug!("Writing {} bytes to serial port {}", value.len(), path);
+    trace!("Writing to serial port {}: {}", path, value);
+    let data = with_line_ending(value.into_bytes(), line_ending)?;
+    let data = with_length_prefix(data, length_prefix)?;
+    let port = path.clone();
+    let rs485 = rs485_flag(state.clone(), &path)?;
+    with_serialport(state, path, command_timeout_ms, move |serialport| {
+        write_data(
+            &window,
+            &port,
+            serialport,
+            &data,
+            flush.unwrap_or(false),
+            complete.unwrap_or(false),
+            rs485.load(Ordering::SeqCst),
+        )
+    })
+}
+
+/// `write` write binary content to the specified serial port
+///
+/// With `command_timeout_ms`, `Error::TimedOut` is returned if the write doesn't complete in time.
+/// With `length_prefix` (`u8`, `u16be`, `u16le`, `u32be` or `u32le`), `value` is preceded by a
+/// header holding its length, and the returned size includes the header.
+/// With `flush`, it only returns once the written bytes have left the output buffer.
+/// Writing continues until all of `value` is written, retrying interrupted writes, unless
+/// `complete` is false, in which case it returns after the first partial write. A write timing out
+/// part way fails with `Error::TimedOut`, whose message tells how many bytes were written.
+#[tauri::command]
+pub fn write_binary<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    value: Vec<u8>,
+    command_timeout_ms: Option<u64>,
+    length_prefix: Option<String>,
+    flush: Option<bool>,
+    complete: Option<bool>,
+) -> Result<usize, Error> {
+    let data = with_length_prefix(value, length_prefix)?;
+    let port = path.clone();
+    let rs485 = rs485_flag(state.clone(), &path)?;
+    with_serialport(state, path, command_timeout_ms, move |serialport| {
+        write_data(
+            &window,
+            &port,
+            serialport,
+            &data,
+            flush.unwrap_or(false),
+            complete.unwrap_or(true),
+            rs485.load(Ordering::SeqCst),
+        )
+    })
+}
+
+/// `write_base64` decodes `value` from standard base64 and writes the bytes to the specified serial
+/// port, returning the number of bytes written. This keeps large binary payloads much smaller on
+/// the IPC bridge than the number array `write_binary` takes. Otherwise it works like
+/// `write_binary`.
+#[tauri::command]
+pub fn write_base64<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    value: String,
+    command_timeout_ms: Option<u64>,
+    flush: Option<bool>,
+    complete: Option<bool>,
+) -> Result<usize, Error> {
+    let data = match BASE64_STANDARD.decode(value.as_bytes()) {
+        Ok(data) => data,
+        Err(error) => return Err(Error::String(format!("Invalid base64 data: {}", error))),
+    };
+    let port = path.clone();
+    let rs485 = rs485_flag(state.clone(), &path)?;
+    with_serialport(state, path, command_timeout_ms, move |serialport| {
+        write_data(
+            &window,
+            &port,
+            serialport,
+            &data,
+            flush.unwrap_or(false),
+            complete.unwrap_or(true),
+            rs485.load(Ordering::SeqCst),
+        )
+    })
+}
+
+/// `write_hex` writes the bytes given as hex digit pairs in `value`, e.g. `"01 03 00 00"`, to the
+/// specified serial port, returning the number of bytes written. Whitespace is ignored and invalid
+/// hex is rejected before anything is written. Otherwise it works like `write_binary`.
+#[tauri::command]
+pub fn write_hex<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    value: String,
+    command_timeout_ms: Option<u64>,
+    flush: Option<bool>,
+    complete: Option<bool>,
+) -> Result<usize, Error> {
+    let data = parse_hex(&value)?;
+    let port = path.clone();
+    let rs485 = rs485_flag(state.clone(), &path)?;
+    with_serialport(state, path, command_timeout_ms, move |serialport| {
+        write_data(
+            &window,
+            &port,
+            serialport,
+            &data,
+            flush.unwrap_or(false),
+            complete.unwrap_or(true),
+            rs485.load(Ordering::SeqCst),
+        )
+    })
+}
+
+/// `write_line` writes `value` followed by `terminator`, a newline by default, to the specified
+/// serial port in one write, returning the number of bytes written including the terminator.
+/// All of it is written as with `write`'s `complete`, and a failing port emits the same
+/// `disconnected` event.
+#[tauri::command]
+pub fn write_line<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    value: String,
+    terminator: Option<String>,
+    command_timeout_ms: Option<u64>,
+) -> Result<usize, Error> {
+    debug!(
+        "Writing a line of {} bytes to serial port {}",
+        value.len(),
+        path
+    );
+    trace!("Writing line to serial port {}: {}", path, value);
+    let mut data = value.into_bytes();
+    data.extend_from_slice(terminator.as_deref().unwrap_or("\n").as_bytes());
+    let port = path.clone();
+    let rs485 = rs485_flag(state.clone(), &path)?;
+    with_serialport(state, path, command_timeout_ms, move |serialport| {
+        write_data(
+            &window,
+            &port,
+            serialport,
+            &data,
+            false,
+            true,
+            rs485.load(Ordering::SeqCst),
+        )
+    })
+}
+
+/// `write_with_timeout` writes all of `value` to the specified serial port, giving up with
+/// `Error::TimedOut` once the port accepts no more data for `timeout_ms`, e.g. when hardware flow
+/// control stalls. The port's timeout is restored afterwards.
+#[tauri::command]
+pub fn write_with_timeout<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    value: Vec<u8>,
+    timeout_ms: u64,
+) -> Result<usize, Error> {
+    get_serialport(state, path.clone(), |serialport_info| {
+        let rs485 = serialport_info.rs485.load(Ordering::SeqCst);
+        let serialport = serialport_info.serialport.as_mut();
+        let previous_timeout = serialport.timeout();
+        if let Err(error) = serialport.set_timeout(Duration::from_millis(timeout_ms)) {
+            return Err(Error::String(format!("Failed to set timeout: {}", error)));
+        }
+        let result = write_data(&window, &path, serialport, &value, false, true, rs485);
+        if let Err(error) = serialport.set_timeout(previous_timeout) {
+            warn!("Failed to restore timeout: {}", error);
+        }
+        if result.is_ok() {
+            serialport_info.touch();
+        }
+        result
+    })
+}
+
+/// `write_async` queues `value` for writing to the specified serial port and returns a write id
+/// without waiting for the write. Writes are performed in order by a writer thread, and their
+/// outcome is reported by `write_status` and a `write-complete` event.
+/// `length_prefix` prepends a length header as in `write`.
+#[tauri::command]
+pub fn write_async<R: Runtime>(
+    _app: AppHandle<R>,
+    window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    value: Vec<u8>,
+    length_prefix: Option<String>,
+) -> Result<u64, Error> {
+    let value = with_length_prefix(value, length_prefix)?;
+    let write_id = state.next_write_id.fetch_add(1, Ordering::SeqCst);
+    let writes = state.writes.clone();
+    get_serialport(state, path.clone(), |serialport_info| {
+        if serialport_info.writer.is_none() {
             match serialport_info.serialport.try_clone() {
-                Ok(mut serial) => {
-                    let event_path = path.replace(".", "");
-                    let read_event = format!("plugin-serialport-read-{}", &event_path);
-                    println!("event: {}", &read_event);
-                    let (tx, rx): (Sender<usize>, Receiver<usize>) = mpsc::channel();
-                    serialport_info.sender = Some(tx);
-                    thread::spawn(move || {
-                        let mut message_buf = String::new(); // Buffer to store the message
-                        loop {
-                            // Check if a signal has been received to stop reading
-                            match rx.try_recv() {
-                                Ok(_) | Err(TryRecvError::Disconnected) => {
-                                    // If a signal is received or the channel is disconnected, break the loop and exit
-                                    println!("Received stop signal for serial port {}", &path);
-                                    break;
-                                }
-                                _ => {} // Continue reading data if no signal received
-                            }
-                            let mut buf = [0; 1]; // Buffer to read a single byte
-                            match serial.read_exact(&mut buf) {
-                                Ok(_) => {
-                                    // Convert the byte to a character
-                                    let character = buf[0] as char;
-                                    // Append the character to the message buffer
-                                    message_buf.push(character);
-                                    
-                                    // Check if a newline character is encountered, indicating the end of a message
-                                    if character == '\n' {
-                                        // Emit the complete message to the frontend
-                                        match window.emit(&read_event, ReadData {
-                                            data: message_buf.as_bytes(),
-                                            size: message_buf.len(),
-                                        }) {
-                                            Ok(_) => {}
-                                            Err(error) => {
-                                                println!("Failed to send data: {}", error)
-                                            }
-                                        }
-                                        
-                                        // Clear the message buffer to prepare for the next message
-                                        message_buf.clear();
-                                    }
-                                }
-                                Err(ref err) if err.kind() == ErrorKind::TimedOut => {
-                                    // Timed out, continue waiting for data
-                                    continue;
-                                }
-                                Err(err) => {
-                                    println!("Failed to read from serial port: {:?}", err);
-                                    break; // Break out of the loop for other errors
-                                }
-                            }
-                        }
-                    });
+                Ok(serial) => {
+                    let (tx, rx) = mpsc::channel();
+                    serialport_info.writer = Some(tx);
+                    spawn_writer(
+                        window,
+                        path.clone(),
+                        serial,
+                        rx,
+                        WriterState {
+                            last_activity: serialport_info.last_activity.clone(),
+                            writes: writes.clone(),
+                            rs485: serialport_info.rs485.clone(),
+                            closing: serialport_info.closing.clone(),
+                        },
+                    );
                 }
                 Err(error) => {
-                    match window.emit(
-                        &disconnected_event,
-                        format!("Serial port {} disconnected!", &path),
-                    ) {
-                        Ok(_) => {}
-                        Err(error) => {
-                            println!("Failed to send disconnection event: {}", error)
-                        }
-                    }
                     return Err(Error::String(format!(
-                        "Failed to read {} serial port: {}",
+                        "Failed to start writing to serial port {}: {}",
                         &path, error
-                    )));
+                    )))
                 }
             }
-            Ok(())
         }
+        match writes.lock() {
+            Ok(mut writes) => {
+                writes.insert(write_id, WriteStatus::Pending);
+            }
+            Err(error) => {
+                return Err(Error::LockPoisoned(format!(
+                    "Failed to acquire lock: {}",
+                    error
+                )))
+            }
+        }
+        if let Some(writer) = &serialport_info.writer {
+            let job = WriteJob {
+                write_id,
+                data: value,
+            };
+            if let Err(error) = writer.send(job) {
+                if let Ok(mut writes) = writes.lock() {
+                    writes.remove(&write_id);
+                }
+                return Err(Error::String(format!(
+                    "Failed to queue data for serial port {}: {}",
+                    &path, error
+                )));
+            }
+        }
+        Ok(write_id)
     })
 }
 
-/// `write` writes to the specified serial port
+/// `write_and_read` writes `value` to the specified serial port and reads the response until it
+/// ends with `delimiter` or is `size` bytes long, whichever comes first. The port stays locked for
+/// the whole exchange, so no other command can write in between. It fails with `Error::TimedOut`
+/// when the response isn't complete within `timeout` milliseconds, the port's read timeout by
+/// default. That also bounds how long `close` waits for the exchange, as it needs the lock too.
+#[tauri::command(async)]
+pub fn write_and_read<R: Runtime>(
+    _app: AppHandle<R>,
+    _window: Window<R>,
+    state: State<'_, SerialportState>,
+    path: String,
+    value: Vec<u8>,
+    delimiter: Option<Vec<u8>>,
+    size: Option<usize>,
+    timeout: Option<u64>,
+) -> Result<WriteAndRead, Error> {
+    match (&delimiter, size) {
+        (None, None) => {
+            return Err(Error::String(
+                "Either a delimiter or a size is required!".to_string(),
+            ))
+        }
+        (Some(delimiter), _) if delimiter.is_empty() => {
+            return Err(Error::String("The delimiter can not be empty!".to_string()))
+        }
+        (_, Some(0)) => return Err(Error::String("The size can not be zero!".to_string())),
+        _ => {}
+    }
+    get_serialport(state, path.clone(), |serialport_info| {
+        if serialport_info.sender.is_some() {
+            return Err(Error::AlreadyReading(format!(
+                "Serial port {} is reading data, cancel the read first!",
+                &path
+            )));
+        }
+        let rs485 = serialport_info.rs485.load(Ordering::SeqCst);
+        let serialport = serialport_info.serialport.as_mut();
+        rs485_transmit(serialport, &path, rs485, |serialport| {
+            match serialport.write_all(&value) {
+                Ok(_) => Ok(()),
+                Err(error) => Err(Error::String(format!(
+                    "Failed to write data to serial port {}: {}",
+                    &path, error
+                ))),
+            }
+        })?;
+        let previous_timeout = serialport.timeout();
+        let deadline = Instant::now()
+            + timeout
+                .map(Duration::from_millis)
+                .unwrap_or(previous_timeout);
+        let response = read_response(
+            serialport,
+            delimiter.as_deref(),
+            size,
+            deadline,
+            &serialport_info.closing,
+        );
+        if let Err(error) = serialport.set_timeout(previous_timeout) {
+            warn!("Failed to restore timeout: {}", error);
+        }
+        let response = response?;
+        serialport_info.touch();
+        Ok(WriteAndRead {
+            written: value.len(),
+            response,
+        })
+    })
+}
+
+/// `write_data_terminal_ready` sets the DTR control line of the specified serial port to `level`
 #[tauri::command]
-pub fn write<R: Runtime>(
+pub fn write_data_terminal_ready<R: Runtime>(
     _app: AppHandle<R>,
     _window: Window<R>,
     state: State<'_, SerialportState>,
     path: String,
-    value: String,
-) -> Result<usize, Error> {
-    let event_path = path.replace(".", "");
-    let disconnected_event = format!("plugin-serialport-disconnected-{}", &event_path);
-    // Print the string that will be written to the serial port
-    println!("Writing to serial port {}: {}", path, value);
+    level: bool,
+) -> Result<(), Error> {
     get_serialport(state, path.clone(), |serialport_info| match serialport_info
         .serialport
-        .write(value.as_bytes())
+        .write_data_terminal_ready(level)
     {
-        Ok(size) => Ok(size),
-        Err(error) => {
-            match _window.emit(
-                &disconnected_event,
-                format!("Serial port {} disconnected!", &path),
-            ) {
-                Ok(_) => {}
-                Err(error) => {
-                    println!("Failed to send disconnection event: {}", error)
-                }
-            }
-            Err(Error::String(format!(
-                "Failed to write data to serial port {}: {}",
-                &path, error
-            )))
-        }
+        Ok(_) => Ok(()),
+        Err(error) => Err(Error::String(format!(
+            "Failed to set DTR of serial port {}: {}",
+            &path, error
+        ))),
     })
 }
 
-/// `write` write binary content to the specified serial port
+/// `write_request_to_send` sets the RTS control line of the specified serial port to `level`
 #[tauri::command]
-pub fn write_binary<R: Runtime>(
+pub fn write_request_to_send<R: Runtime>(
     _app: AppHandle<R>,
     _window: Window<R>,
     state: State<'_, SerialportState>,
     path: String,
-    value: Vec<u8>,
-) -> Result<usize, Error> {
+    level: bool,
+) -> Result<(), Error> {
     get_serialport(state, path.clone(), |serialport_info| match serialport_info
         .serialport
-        .write(&value)
+        .write_request_to_send(level)
     {
-        Ok(size) => Ok(size),
+        Ok(_) => Ok(()),
         Err(error) => Err(Error::String(format!(
-            "Failed to write data to serial port {}: {}",
+            "Failed to set RTS of serial port {}: {}",
             &path, error
         ))),
     })
 }
+
+/// `write_status` returns the status of a write queued by `write_async`. Once a finished status
+/// has been returned the write is forgotten, and later calls with its id fail. Only the latest
+/// 1024 finished writes are kept, so the status of an older write may be gone before it is polled.
+#[tauri::command]
+pub fn write_status(
+    state: State<'_, SerialportState>,
+    write_id: u64,
+) -> Result<WriteStatus, Error> {
+    match state.writes.lock() {
+        Ok(mut writes) => match writes.remove(&write_id) {
+            Some(WriteStatus::Pending) => {
+                writes.insert(write_id, WriteStatus::Pending);
+                Ok(WriteStatus::Pending)
+            }
+            Some(status) => Ok(status),
+            None => Err(Error::String(format!("Unknown write id {}", write_id))),
+        },
+        Err(error) => Err(Error::LockPoisoned(format!(
+            "Failed to acquire lock: {}",
+            error
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{line_options, MockPort, Recorder};
+
+    #[test]
+    fn malformed_banner_settings_are_rejected() {
+        let banner = Regex::new(r"(?P<data_bits>\w)(?P<parity>\w)(?P<stop_bits>\w)").unwrap();
+        let mut port = MockPort::new();
+        let captures = banner.captures("8N1").unwrap();
+        assert!(apply_banner_settings(&mut port, &captures).is_ok());
+        for settings in ["9N1", "xN1", "8N3"] {
+            let captures = banner.captures(settings).unwrap();
+            let error = apply_banner_settings(&mut port, &captures).unwrap_err();
+            assert_eq!(error.kind(), "InvalidInput", "{}", settings);
+        }
+    }
+
+    #[test]
+    fn get_stop_bits_defaults_to_one() {
+        assert_eq!(get_stop_bits(None).unwrap(), StopBits::One);
+        assert_eq!(get_stop_bits(Some(1)).unwrap(), StopBits::One);
+        assert_eq!(get_stop_bits(Some(2)).unwrap(), StopBits::Two);
+        assert!(get_stop_bits(Some(3)).is_err());
+    }
+
+    #[test]
+    fn parse_hex_ignores_whitespace_and_case() {
+        assert_eq!(
+            parse_hex("48 65\n6C6c 6F").unwrap(),
+            vec![0x48, 0x65, 0x6c, 0x6c, 0x6f]
+        );
+        assert_eq!(parse_hex("").unwrap(), Vec::<u8>::new());
+    }
+
+    #[test]
+    fn parse_hex_rejects_invalid_data() {
+        assert!(parse_hex("486").is_err());
+        assert!(parse_hex("4g").is_err());
+    }
+
+    #[test]
+    fn get_flow_control_ignores_case() {
+        assert_eq!(get_flow_control(None).unwrap(), FlowControl::None);
+        assert_eq!(
+            get_flow_control(Some("Software".to_string())).unwrap(),
+            FlowControl::Software
+        );
+        assert_eq!(
+            get_flow_control(Some("HARDWARE".to_string())).unwrap(),
+            FlowControl::Hardware
+        );
+        assert!(get_flow_control(Some("rts".to_string())).is_err());
+    }
+
+    #[test]
+    fn get_parity_ignores_case() {
+        assert_eq!(get_parity(None).unwrap(), Parity::None);
+        assert_eq!(get_parity(Some("ODD".to_string())).unwrap(), Parity::Odd);
+        assert_eq!(get_parity(Some("Even".to_string())).unwrap(), Parity::Even);
+        // Set up by `open_port`, on top of no parity
+        assert_eq!(get_parity(Some("Mark".to_string())).unwrap(), Parity::None);
+        assert!(get_parity(Some("7E1".to_string())).is_err());
+    }
+
+    #[test]
+    fn write_fully_retries_short_and_interrupted_writes() {
+        // Far more than the transmit FIFO of a typical UART takes at once
+        let data: Vec<u8> = (0..4096).map(|i| i as u8).collect();
+        let mut port = MockPort::new();
+        port.write_limit = 16;
+        port.interrupt = true;
+        assert_eq!(write_fully(&mut port, &data).unwrap(), data.len());
+        assert_eq!(*port.written.lock().unwrap(), data);
+    }
+
+    #[test]
+    fn write_fully_reports_how_much_was_written_before_a_timeout() {
+        let mut port = MockPort::new();
+        port.write_limit = 16;
+        port.capacity = Some(100);
+        let (written, error) = write_fully(&mut port, &[0; 4096]).unwrap_err();
+        assert_eq!(written, 100);
+        assert_eq!(error.kind(), ErrorKind::TimedOut);
+    }
+
+    #[test]
+    fn readers_can_be_cancelled_and_restarted_rapidly() {
+        let recorder = Recorder::default();
+        let port = MockPort::new();
+        let written = port.written.clone();
+        let mut serialport_info = SerialportInfo::new(Box::new(port));
+        for _ in 0..50 {
+            spawn_port_reader(
+                recorder.clone(),
+                "mock",
+                &mut serialport_info,
+                line_options(),
+                None,
+            )
+            .unwrap();
+            // The reader reads from its own clone of the port
+            assert_eq!(Arc::strong_count(&written), 3);
+            // Stopped as `cancel_read` does
+            let reader = take_reader(&mut serialport_info).unwrap();
+            assert!(serialport_info.sender.is_none());
+            join_reader(reader, "mock").unwrap();
+            // Its clone is released once it was joined
+            assert_eq!(Arc::strong_count(&written), 2);
+        }
+        assert!(take_reader(&mut serialport_info).is_none());
+        assert!(recorder.events.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn join_reader_gives_up_on_a_stuck_reader() {
+        let (tx, rx) = mpsc::channel::<()>();
+        let reader = StoppedReader {
+            handle: thread::spawn(move || {
+                let _ = rx.recv();
+            }),
+            join_timeout: Duration::from_millis(100),
+        };
+        assert!(matches!(
+            join_reader(reader, "mock"),
+            Err(Error::TimedOut(_))
+        ));
+        drop(tx);
+    }
+
+    #[test]
+    fn take_reader_waits_longer_for_a_long_read_timeout() {
+        let mut port = MockPort::new();
+        port.set_timeout(Duration::from_secs(3)).unwrap();
+        let mut serialport_info = SerialportInfo::new(Box::new(port));
+        serialport_info.reader = Some(thread::spawn(|| {}));
+        let reader = take_reader(&mut serialport_info).unwrap();
+        assert_eq!(
+            reader.join_timeout,
+            Duration::from_secs(3) + INTERRUPT_INTERVAL
+        );
+        join_reader(reader, "mock").unwrap();
+
+        serialport_info.reader = Some(thread::spawn(|| {}));
+        serialport_info
+            .serialport
+            .set_timeout(Duration::from_millis(10))
+            .unwrap();
+        assert_eq!(
+            take_reader(&mut serialport_info).unwrap().join_timeout,
+            JOIN_TIMEOUT
+        );
+    }
+
+    #[test]
+    fn mark_space_ignores_case() {
+        assert_eq!(mark_space(Some("MARK")), Some(true));
+        assert_eq!(mark_space(Some("Space")), Some(false));
+        assert_eq!(mark_space(Some("odd")), None);
+        assert_eq!(mark_space(None), None);
+    }
+}
@@ -0,0 +1,99 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Decompression of frames received from devices that compress their output.
+
+use crate::error::Error;
+
+/// Format of a compressed frame
+#[derive(Clone, Copy, Debug)]
+pub enum Compression {
+    Gzip,
+    Zlib,
+    Deflate,
+}
+
+impl Compression {
+    /// `parse` reads the format from its name: `gzip`, `zlib` or `deflate`. Fails when the plugin
+    /// was built without the `decompress` feature.
+    pub fn parse(value: &str) -> Result<Self, Error> {
+        let compression = match value {
+            "gzip" => Compression::Gzip,
+            "zlib" => Compression::Zlib,
+            "deflate" => Compression::Deflate,
+            _ => {
+                return Err(Error::String(format!(
+                    "Invalid compression {}, expected gzip, zlib or deflate",
+                    value
+                )))
+            }
+        };
+        if cfg!(feature = "decompress") {
+            Ok(compression)
+        } else {
+            Err(Error::String(
+                "Decompression requires the decompress feature of the plugin".to_string(),
+            ))
+        }
+    }
+
+    /// `decompress` returns the decompressed content of `data`. Content longer than `limit` bytes
+    /// fails with `ErrorKind::InvalidData`, so a small frame can't expand without bound.
+    #[cfg(feature = "decompress")]
+    pub fn decompress(self, data: &[u8], limit: usize) -> std::io::Result<Vec<u8>> {
+        use flate2::read::{DeflateDecoder, GzDecoder, ZlibDecoder};
+        use std::io::{Error, ErrorKind, Read};
+
+        let decoder: Box<dyn Read + '_> = match self {
+            Compression::Gzip => Box::new(GzDecoder::new(data)),
+            Compression::Zlib => Box::new(ZlibDecoder::new(data)),
+            Compression::Deflate => Box::new(DeflateDecoder::new(data)),
+        };
+        let mut decompressed = Vec::new();
+        // One byte more than allowed tells an oversized frame apart from one of exactly `limit`
+        decoder
+            .take(limit as u64 + 1)
+            .read_to_end(&mut decompressed)?;
+        if decompressed.len() > limit {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("decompressed frame exceeds {} bytes", limit),
+            ));
+        }
+        Ok(decompressed)
+    }
+
+    #[cfg(not(feature = "decompress"))]
+    pub fn decompress(self, _data: &[u8], _limit: usize) -> std::io::Result<Vec<u8>> {
+        unreachable!("`parse` only succeeds with the decompress feature")
+    }
+}
+
+#[cfg(all(test, feature = "decompress"))]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use std::io::Write;
+
+    fn gzip(data: &[u8]) -> Vec<u8> {
+        let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data).unwrap();
+        encoder.finish().unwrap()
+    }
+
+    #[test]
+    fn decompress_returns_the_content() {
+        let frame = gzip(b"hello");
+        assert_eq!(Compression::Gzip.decompress(&frame, 5).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn decompress_rejects_content_over_the_limit() {
+        // A few hundred bytes that expand to a megabyte
+        let frame = gzip(&vec![0; 1 << 20]);
+        assert!(frame.len() < 4096);
+        let error = Compression::Gzip.decompress(&frame, 4096).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    }
+}
@@ -1,4 +1,4 @@
-use serde::{Serialize, Serializer};
+use serde::{ser::SerializeStruct, Serialize, Serializer};
 
 #[derive(Serialize, Clone)]
 pub struct InvokeResult {
@@ -18,13 +18,81 @@ pub enum Error {
     Io(#[from] std::io::Error),
     #[error("{0}")]
     String(String),
+    /// The port does not exist.
+    #[error("{0}")]
+    PortNotFound(String),
+    /// The port is already open.
+    #[error("{0}")]
+    PortAlreadyOpen(String),
+    /// The port is held exclusively by another process.
+    #[error("{0}")]
+    PortBusy(String),
+    /// The port is not open.
+    #[error("{0}")]
+    NotOpen(String),
+    /// The port is being read by a reader, which has to be cancelled first.
+    #[error("{0}")]
+    AlreadyReading(String),
+    /// A lock on the plugin state was poisoned by a panic while it was held.
+    #[error("{0}")]
+    LockPoisoned(String),
+    /// The device did not respond in time.
+    #[error("{0}")]
+    TimedOut(String),
+    /// The port was closed while the operation was in progress.
+    #[error("{0}")]
+    Cancelled(String),
+    /// A value received from the device or the caller is malformed.
+    #[error("{0}")]
+    InvalidInput(String),
+}
+
+impl Error {
+    /// `kind` names the variant, so the frontend can tell errors apart without parsing messages
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Io(_) => "Io",
+            Error::String(_) => "Other",
+            Error::PortNotFound(_) => "PortNotFound",
+            Error::PortAlreadyOpen(_) => "PortAlreadyOpen",
+            Error::PortBusy(_) => "PortBusy",
+            Error::NotOpen(_) => "NotOpen",
+            Error::AlreadyReading(_) => "AlreadyReading",
+            Error::LockPoisoned(_) => "LockPoisoned",
+            Error::TimedOut(_) => "TimedOut",
+            Error::Cancelled(_) => "Cancelled",
+            Error::InvalidInput(_) => "InvalidInput",
+        }
+    }
+
+    /// `context` prefixes the message with `context`, keeping the kind
+    pub fn context(self, context: &str) -> Self {
+        let message = format!("{}: {}", context, self);
+        match self {
+            Error::Io(error) => Error::Io(std::io::Error::new(error.kind(), message)),
+            Error::String(_) => Error::String(message),
+            Error::PortNotFound(_) => Error::PortNotFound(message),
+            Error::PortAlreadyOpen(_) => Error::PortAlreadyOpen(message),
+            Error::PortBusy(_) => Error::PortBusy(message),
+            Error::NotOpen(_) => Error::NotOpen(message),
+            Error::AlreadyReading(_) => Error::AlreadyReading(message),
+            Error::LockPoisoned(_) => Error::LockPoisoned(message),
+            Error::TimedOut(_) => Error::TimedOut(message),
+            Error::Cancelled(_) => Error::Cancelled(message),
+            Error::InvalidInput(_) => Error::InvalidInput(message),
+        }
+    }
 }
 
+/// Errors reach the frontend as `{ kind, message }`
 impl Serialize for Error {
     fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        serializer.serialize_str(self.to_string().as_ref())
+        let mut error = serializer.serialize_struct("Error", 2)?;
+        error.serialize_field("kind", self.kind())?;
+        error.serialize_field("message", &self.to_string())?;
+        error.end()
     }
 }
@@ -0,0 +1,119 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::state::PortEvent;
+use log::error;
+use serde::Serialize;
+use tauri::{EventTarget, Manager, Runtime, Window};
+
+pub const READ_EVENT: &str = "read";
+pub const DISCONNECTED_EVENT: &str = "disconnected";
+pub const ERROR_EVENT: &str = "error";
+pub const AUTO_CLOSED_EVENT: &str = "auto-closed";
+pub const STATS_EVENT: &str = "stats";
+pub const BREAK_EVENT: &str = "break";
+pub const SEQUENCE_GAP_EVENT: &str = "sequence-gap";
+pub const WRITE_COMPLETE_EVENT: &str = "write-complete";
+pub const RECONNECTING_EVENT: &str = "reconnecting";
+pub const RECONNECTED_EVENT: &str = "reconnected";
+pub const RECONNECT_FAILED_EVENT: &str = "reconnect-failed";
+// Emitted by the hot-plug scanner under a name without the port, see `emit_scan_event`
+pub const PORT_ADDED_EVENT: &str = "port-added";
+pub const PORT_REMOVED_EVENT: &str = "port-removed";
+
+/// Every event type the plugin emits for a port
+pub const EVENT_TYPES: &[&str] = &[
+    READ_EVENT,
+    DISCONNECTED_EVENT,
+    ERROR_EVENT,
+    AUTO_CLOSED_EVENT,
+    STATS_EVENT,
+    BREAK_EVENT,
+    SEQUENCE_GAP_EVENT,
+    WRITE_COMPLETE_EVENT,
+    RECONNECTING_EVENT,
+    RECONNECTED_EVENT,
+    RECONNECT_FAILED_EVENT,
+];
+
+/// `get_event_name` builds the name of the `event_type` event emitted for `path`
+pub fn get_event_name(event_type: &str, path: &str) -> String {
+    format!("plugin-serialport-{}-{}", event_type, path.replace(".", ""))
+}
+
+/// `emit_event` emits an `event_type` event for `path` wrapped in a `PortEvent`
+pub fn emit_event<R: Runtime, T: Serialize + Clone>(
+    window: &Window<R>,
+    event_type: &'static str,
+    path: &str,
+    payload: T,
+) {
+    emit_event_to(window, None, event_type, path, payload)
+}
+
+/// `emit_event_to` emits like `emit_event`, but only to the webview window labelled `label` when
+/// one is given
+pub fn emit_event_to<R: Runtime, T: Serialize + Clone>(
+    window: &Window<R>,
+    label: Option<&str>,
+    event_type: &'static str,
+    path: &str,
+    payload: T,
+) {
+    let event = PortEvent {
+        path: path.to_string(),
+        event_type,
+        payload,
+    };
+    let event_name = get_event_name(event_type, path);
+    let result = match label {
+        Some(label) => window.emit_to(EventTarget::webview_window(label), &event_name, event),
+        None => window.emit(&event_name, event),
+    };
+    if let Err(error) = result {
+        error!("Failed to send {} event: {}", event_type, error)
+    }
+}
+
+/// Where the read thread emits its events, the window that started it outside of unit tests
+pub trait EventSink: Send + 'static {
+    /// `emit_port_event` emits like `emit_event_to`
+    fn emit_port_event<T: Serialize + Clone>(
+        &self,
+        label: Option<&str>,
+        event_type: &'static str,
+        path: &str,
+        payload: T,
+    );
+}
+
+impl<R: Runtime> EventSink for Window<R> {
+    fn emit_port_event<T: Serialize + Clone>(
+        &self,
+        label: Option<&str>,
+        event_type: &'static str,
+        path: &str,
+        payload: T,
+    ) {
+        emit_event_to(self, label, event_type, path, payload)
+    }
+}
+
+/// `emit_scan_event` emits an `event_type` event of the hot-plug scanner for `path`, wrapped in a
+/// `PortEvent`. Its name is `plugin-serialport-<event_type>`, so one listener sees every port.
+pub fn emit_scan_event<R: Runtime, T: Serialize + Clone>(
+    window: &Window<R>,
+    event_type: &'static str,
+    path: &str,
+    payload: T,
+) {
+    let event = PortEvent {
+        path: path.to_string(),
+        event_type,
+        payload,
+    };
+    if let Err(error) = window.emit(&format!("plugin-serialport-{}", event_type), event) {
+        error!("Failed to send {} event: {}", event_type, error)
+    }
+}
@@ -0,0 +1,124 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Length-prefixed framing, where each payload is preceded by a header holding its length.
+
+use crate::error::Error;
+
+/// Format of the length header in front of a payload
+#[derive(Clone, Copy, Debug)]
+pub enum LengthPrefix {
+    U8,
+    U16Be,
+    U16Le,
+    U32Be,
+    U32Le,
+}
+
+impl LengthPrefix {
+    /// `parse` reads the format from its name: `u8`, `u16be`, `u16le`, `u32be` or `u32le`
+    pub fn parse(value: &str) -> Result<Self, Error> {
+        match value {
+            "u8" => Ok(LengthPrefix::U8),
+            "u16be" => Ok(LengthPrefix::U16Be),
+            "u16le" => Ok(LengthPrefix::U16Le),
+            "u32be" => Ok(LengthPrefix::U32Be),
+            "u32le" => Ok(LengthPrefix::U32Le),
+            _ => Err(Error::String(format!(
+                "Invalid length prefix {}, expected u8, u16be, u16le, u32be or u32le",
+                value
+            ))),
+        }
+    }
+
+    /// `size` is the length of the header in bytes
+    pub fn size(self) -> usize {
+        match self {
+            LengthPrefix::U8 => 1,
+            LengthPrefix::U16Be | LengthPrefix::U16Le => 2,
+            LengthPrefix::U32Be | LengthPrefix::U32Le => 4,
+        }
+    }
+
+    /// `decode` reads the payload length from a header of `size` bytes
+    pub fn decode(self, header: &[u8]) -> usize {
+        match self {
+            LengthPrefix::U8 => header[0] as usize,
+            LengthPrefix::U16Be => u16::from_be_bytes([header[0], header[1]]) as usize,
+            LengthPrefix::U16Le => u16::from_le_bytes([header[0], header[1]]) as usize,
+            LengthPrefix::U32Be => {
+                u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize
+            }
+            LengthPrefix::U32Le => {
+                u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize
+            }
+        }
+    }
+
+    /// `prepend` returns `payload` preceded by a header holding its length
+    pub fn prepend(self, payload: &[u8]) -> Result<Vec<u8>, Error> {
+        let len = payload.len();
+        let too_long = || {
+            Error::String(format!(
+                "Payload of {} bytes is too long for a {:?} length prefix",
+                len, self
+            ))
+        };
+        let mut frame = match self {
+            LengthPrefix::U8 => vec![u8::try_from(len).map_err(|_| too_long())?],
+            LengthPrefix::U16Be => u16::try_from(len)
+                .map_err(|_| too_long())?
+                .to_be_bytes()
+                .to_vec(),
+            LengthPrefix::U16Le => u16::try_from(len)
+                .map_err(|_| too_long())?
+                .to_le_bytes()
+                .to_vec(),
+            LengthPrefix::U32Be => u32::try_from(len)
+                .map_err(|_| too_long())?
+                .to_be_bytes()
+                .to_vec(),
+            LengthPrefix::U32Le => u32::try_from(len)
+                .map_err(|_| too_long())?
+                .to_le_bytes()
+                .to_vec(),
+        };
+        frame.extend_from_slice(payload);
+        Ok(frame)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prepend_and_decode_round_trip() {
+        for name in ["u8", "u16be", "u16le", "u32be", "u32le"] {
+            let length_prefix = LengthPrefix::parse(name).unwrap();
+            let frame = length_prefix.prepend(b"hello").unwrap();
+            assert_eq!(frame.len(), length_prefix.size() + 5);
+            let (header, payload) = frame.split_at(length_prefix.size());
+            assert_eq!(length_prefix.decode(header), 5);
+            assert_eq!(payload, b"hello");
+        }
+    }
+
+    #[test]
+    fn prepend_uses_the_byte_order() {
+        assert_eq!(LengthPrefix::U16Be.prepend(&[0; 258]).unwrap()[..2], [1, 2]);
+        assert_eq!(LengthPrefix::U16Le.prepend(&[0; 258]).unwrap()[..2], [2, 1]);
+    }
+
+    #[test]
+    fn prepend_rejects_payloads_too_long_for_the_header() {
+        assert!(LengthPrefix::U8.prepend(&[0; 255]).is_ok());
+        assert!(LengthPrefix::U8.prepend(&[0; 256]).is_err());
+    }
+
+    #[test]
+    fn parse_rejects_unknown_formats() {
+        assert!(LengthPrefix::parse("u64be").is_err());
+    }
+}
@@ -12,8 +12,10 @@
 )]
 
 use crate::commands::*;
+pub use crate::state::SerialPortOptions;
 use crate::state::SerialportState;
 use std::collections::HashMap;
+use std::sync::atomic::AtomicU64;
 use std::sync::{Arc, Mutex};
 use tauri::{
     plugin::{Builder, TauriPlugin},
@@ -21,31 +23,107 @@ use tauri::{
 };
 
 mod commands;
+mod compression;
 mod error;
+mod events;
+mod framing;
+#[cfg(test)]
+mod mock;
+mod platform;
+mod reader;
 mod state;
+mod writer;
 
 pub fn init<R: Runtime>() -> TauriPlugin<R> {
+    init_with_options(SerialPortOptions::default())
+}
+
+/// Initializes the plugin with `defaults` applied to every `open` call.
+///
+/// ```rust,ignore
+/// tauri_plugin_serialplugin::init_with_options(SerialPortOptions {
+///     data_bits: Some(7),
+///     parity: Some("Even".to_string()),
+///     stop_bits: Some(1),
+///     ..Default::default()
+/// })
+/// ```
+pub fn init_with_options<R: Runtime>(defaults: SerialPortOptions) -> TauriPlugin<R> {
     Builder::new("serialplugin")
         .js_init_script(include_str!("api-iife.js").to_string())
         .invoke_handler(tauri::generate_handler![
             available_ports,
+            available_ports_cached,
+            available_ports_detailed,
+            bytes_to_read,
+            bytes_to_write,
             cancel_read,
+            clear_break,
+            clear_buffer,
             close,
             close_all,
+            connect_device,
+            flush,
             force_close,
+            force_unlock,
+            get_last_frame,
+            get_latency_timer,
+            get_port_config,
+            get_port_settings,
+            get_timeout,
+            is_open,
+            list_events_for_port,
+            list_open_ports,
             open,
+            open_autoconfig,
+            open_fd,
+            ping_device,
+            port_driver_info,
             read,
+            read_available,
+            read_buffer_len,
+            read_control_signals,
+            read_once,
+            read_stats,
+            reconnect,
+            record_traffic,
+            recommended_chunk_size,
+            runtime_info,
+            send_break,
+            set_baud_rate,
+            set_break,
+            set_latency_timer,
+            set_read_target,
+            set_rs485,
+            set_timeout,
+            start_port_scan,
+            stop_and_join_reader,
+            stop_port_scan,
             write,
+            write_and_read,
+            write_async,
+            write_base64,
             write_binary,
+            write_data_terminal_ready,
+            write_hex,
+            write_line,
+            write_request_to_send,
+            write_status,
+            write_with_timeout,
         ])
-        .setup(|app, _| {
+        .setup(move |app, _| {
             let serialports = Arc::new(Mutex::new(HashMap::new()));
 
             // Wrap the HashMap in a SerialportState struct
             let serialport_state = SerialportState {
                 serialports: serialports.clone(), // Cloning Arc to share ownership
+                defaults,
+                ports_cache: Mutex::new(HashMap::new()),
+                writes: Arc::new(Mutex::new(HashMap::new())),
+                next_write_id: AtomicU64::new(0),
+                port_scan: Mutex::new(None),
             };
-        
+
             // Manage the SerialportState in the Tauri application
             app.manage(serialport_state);
             Ok(())
@@ -0,0 +1,234 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! An in-memory `SerialPort` and an `EventSink` for unit tests.
+
+use crate::events::{EventSink, READ_EVENT};
+use crate::reader::{Encoding, ReadMode, ReaderOptions};
+use serde::Serialize;
+use serialport::{ClearBuffer, DataBits, FlowControl, Parity, SerialPort, StopBits};
+use std::io::{self, ErrorKind};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// A port that keeps what is written to it and never receives data. Clones share the written
+/// bytes, like clones of a real port share the device.
+#[derive(Clone)]
+pub struct MockPort {
+    /// Bytes written so far
+    pub written: Arc<Mutex<Vec<u8>>>,
+    /// Most bytes a single `write` takes, like a small transmit FIFO
+    pub write_limit: usize,
+    /// Bytes taken in total before `write` times out, as on a stalled port
+    pub capacity: Option<usize>,
+    /// Interrupt every other `write` before it takes any data
+    pub interrupt: bool,
+    interrupted: bool,
+    timeout: Duration,
+}
+
+impl MockPort {
+    pub fn new() -> Self {
+        MockPort {
+            written: Arc::new(Mutex::new(Vec::new())),
+            write_limit: usize::MAX,
+            capacity: None,
+            interrupt: false,
+            interrupted: false,
+            timeout: Duration::from_millis(200),
+        }
+    }
+}
+
+/// `line_options` are the options of a reader splitting lines at `\n`, keeping it
+pub fn line_options() -> ReaderOptions {
+    ReaderOptions {
+        mode: ReadMode::Line,
+        delimiter: b"\n".to_vec(),
+        include_delimiter: true,
+        length_prefix: None,
+        max_frame_len: 1024,
+        max_message_size: 1024,
+        timeout: None,
+        max_latency: None,
+        stats_interval: None,
+        encoding: Encoding::Bytes,
+        hexdump: false,
+        target_label: None,
+        detect_break: false,
+        decompress: None,
+        retain_last_frame: false,
+        strip_bytes: Vec::new(),
+        sequence_field: None,
+    }
+}
+
+/// An `EventSink` that keeps the events emitted to it, with their payload as JSON
+#[derive(Clone, Default)]
+pub struct Recorder {
+    pub events: Arc<Mutex<Vec<(&'static str, serde_json::Value)>>>,
+}
+
+impl Recorder {
+    /// `frames` returns the data of the `read` events emitted so far, read with `Encoding::Bytes`
+    pub fn frames(&self) -> Vec<Vec<u8>> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(event_type, _)| *event_type == READ_EVENT)
+            .map(|(_, payload)| serde_json::from_value(payload["data"].clone()).unwrap())
+            .collect()
+    }
+}
+
+impl EventSink for Recorder {
+    fn emit_port_event<T: Serialize + Clone>(
+        &self,
+        _label: Option<&str>,
+        event_type: &'static str,
+        _path: &str,
+        payload: T,
+    ) {
+        let payload = serde_json::to_value(payload).unwrap();
+        self.events.lock().unwrap().push((event_type, payload));
+    }
+}
+
+impl io::Read for MockPort {
+    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+        thread::sleep(self.timeout.min(Duration::from_millis(10)));
+        Err(io::Error::from(ErrorKind::TimedOut))
+    }
+}
+
+impl io::Write for MockPort {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.interrupt {
+            self.interrupted = !self.interrupted;
+            if self.interrupted {
+                return Err(io::Error::from(ErrorKind::Interrupted));
+            }
+        }
+        let mut written = self.written.lock().unwrap();
+        let room = match self.capacity {
+            Some(capacity) => capacity.saturating_sub(written.len()),
+            None => usize::MAX,
+        };
+        if room == 0 && !buf.is_empty() {
+            return Err(io::Error::from(ErrorKind::TimedOut));
+        }
+        let size = buf.len().min(self.write_limit).min(room);
+        written.extend_from_slice(&buf[..size]);
+        Ok(size)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl SerialPort for MockPort {
+    fn name(&self) -> Option<String> {
+        Some("mock".to_string())
+    }
+
+    fn baud_rate(&self) -> serialport::Result<u32> {
+        Ok(9600)
+    }
+
+    fn data_bits(&self) -> serialport::Result<DataBits> {
+        Ok(DataBits::Eight)
+    }
+
+    fn flow_control(&self) -> serialport::Result<FlowControl> {
+        Ok(FlowControl::None)
+    }
+
+    fn parity(&self) -> serialport::Result<Parity> {
+        Ok(Parity::None)
+    }
+
+    fn stop_bits(&self) -> serialport::Result<StopBits> {
+        Ok(StopBits::One)
+    }
+
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    fn set_baud_rate(&mut self, _baud_rate: u32) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn set_data_bits(&mut self, _data_bits: DataBits) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn set_flow_control(&mut self, _flow_control: FlowControl) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn set_parity(&mut self, _parity: Parity) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn set_stop_bits(&mut self, _stop_bits: StopBits) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn set_timeout(&mut self, timeout: Duration) -> serialport::Result<()> {
+        self.timeout = timeout;
+        Ok(())
+    }
+
+    fn write_request_to_send(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn write_data_terminal_ready(&mut self, _level: bool) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn read_clear_to_send(&mut self) -> serialport::Result<bool> {
+        Ok(false)
+    }
+
+    fn read_data_set_ready(&mut self) -> serialport::Result<bool> {
+        Ok(false)
+    }
+
+    fn read_ring_indicator(&mut self) -> serialport::Result<bool> {
+        Ok(false)
+    }
+
+    fn read_carrier_detect(&mut self) -> serialport::Result<bool> {
+        Ok(false)
+    }
+
+    fn bytes_to_read(&self) -> serialport::Result<u32> {
+        Ok(0)
+    }
+
+    fn bytes_to_write(&self) -> serialport::Result<u32> {
+        Ok(0)
+    }
+
+    fn clear(&self, _buffer_to_clear: ClearBuffer) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn try_clone(&self) -> serialport::Result<Box<dyn SerialPort>> {
+        Ok(Box::new(self.clone()))
+    }
+
+    fn set_break(&self) -> serialport::Result<()> {
+        Ok(())
+    }
+
+    fn clear_break(&self) -> serialport::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,260 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+//! Platform specific port details that the `serialport` crate does not expose.
+
+use crate::error::Error;
+use std::collections::HashMap;
+
+/// `sysfs_device` returns the sysfs directory of the device behind the tty at `path`
+#[cfg(target_os = "linux")]
+fn sysfs_device(path: &str) -> std::path::PathBuf {
+    let name = std::path::Path::new(path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string());
+    std::path::PathBuf::from("/sys/class/tty")
+        .join(name)
+        .join("device")
+}
+
+/// `driver_info` adds the kernel driver details sysfs exposes for the tty
+#[cfg(target_os = "linux")]
+pub fn driver_info(path: &str, info: &mut HashMap<String, String>) {
+    let device = sysfs_device(path);
+    // Each of these is a symlink whose target's file name is the value, e.g. `driver -> ftdi_sio`
+    for (key, link) in [
+        ("driver", "driver"),
+        ("subsystem", "subsystem"),
+        ("module", "driver/module"),
+    ] {
+        if let Ok(target) = std::fs::read_link(device.join(link)) {
+            if let Some(value) = target.file_name() {
+                info.insert(key.to_string(), value.to_string_lossy().to_string());
+            }
+        }
+    }
+    if let Ok(interface) = std::fs::read_to_string(device.join("interface")) {
+        info.insert("interface".to_string(), interface.trim().to_string());
+    }
+}
+
+/// `latency_timer` reads the `latency_timer` attribute the `ftdi_sio` driver exposes
+#[cfg(target_os = "linux")]
+pub fn latency_timer(path: &str) -> Result<u8, Error> {
+    let attribute = sysfs_device(path).join("latency_timer");
+    match std::fs::read_to_string(&attribute) {
+        Ok(value) => match value.trim().parse::<u8>() {
+            Ok(value) => Ok(value),
+            Err(_) => Err(Error::String(format!(
+                "Invalid latency timer value: {}",
+                value.trim()
+            ))),
+        },
+        Err(error) => Err(Error::String(format!(
+            "Serial port {} has no latency timer (not an FTDI adapter?): {}",
+            path, error
+        ))),
+    }
+}
+
+/// `set_latency_timer` writes the `latency_timer` attribute the `ftdi_sio` driver exposes
+#[cfg(target_os = "linux")]
+pub fn set_latency_timer(path: &str, value: u8) -> Result<(), Error> {
+    let attribute = sysfs_device(path).join("latency_timer");
+    match std::fs::write(&attribute, value.to_string()) {
+        Ok(_) => Ok(()),
+        Err(error) => Err(Error::String(format!(
+            "Failed to set the latency timer of serial port {}: {}",
+            path, error
+        ))),
+    }
+}
+
+/// `open_shared` opens the port configured by `builder` without the `TIOCEXCL` lock `open` takes,
+/// so other processes can open it as well
+#[cfg(unix)]
+pub fn open_shared(
+    builder: serialport::SerialPortBuilder,
+) -> serialport::Result<Box<dyn serialport::SerialPort>> {
+    let mut port = builder.open_native()?;
+    port.set_exclusive(false)?;
+    Ok(Box::new(port))
+}
+
+/// `open_shared` is a plain `open` on Windows, where COM ports can't be shared
+#[cfg(not(unix))]
+pub fn open_shared(
+    builder: serialport::SerialPortBuilder,
+) -> serialport::Result<Box<dyn serialport::SerialPort>> {
+    builder.open()
+}
+
+/// `open_mark_space` opens the port configured by `builder` with mark parity (`mark`) or space
+/// parity, see `set_mark_space`
+#[cfg(target_os = "linux")]
+pub fn open_mark_space(
+    builder: serialport::SerialPortBuilder,
+    mark: bool,
+    exclusive: bool,
+) -> serialport::Result<Box<dyn serialport::SerialPort>> {
+    let mut port = builder.parity(serialport::Parity::None).open_native()?;
+    if !exclusive {
+        port.set_exclusive(false)?;
+    }
+    set_mark_space(&port, mark)?;
+    Ok(Box::new(port))
+}
+
+/// `set_mark_space` switches `port` to mark parity (`mark`) or space parity, which termios
+/// supports through `CMSPAR`: with it set, `PARODD` selects a parity bit that is always 1 instead
+/// of odd parity, and a parity bit that is always 0 otherwise.
+#[cfg(target_os = "linux")]
+pub fn set_mark_space(port: &serialport::TTYPort, mark: bool) -> serialport::Result<()> {
+    use std::os::fd::AsRawFd;
+
+    let fd = port.as_raw_fd();
+    // SAFETY: `termios` is plain data that `tcgetattr` fills in, and `fd` is open for as long as
+    // `port` is alive
+    unsafe {
+        let mut termios: libc::termios = std::mem::zeroed();
+        if libc::tcgetattr(fd, &mut termios) != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        termios.c_cflag |= libc::PARENB | libc::CMSPAR;
+        if mark {
+            termios.c_cflag |= libc::PARODD;
+        } else {
+            termios.c_cflag &= !libc::PARODD;
+        }
+        if libc::tcsetattr(fd, libc::TCSANOW, &termios) != 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+const FTDIBUS: &str = r"SYSTEM\CurrentControlSet\Enum\FTDIBUS";
+
+/// `driver_info` adds the driver device the registry maps to the COM port
+#[cfg(windows)]
+pub fn driver_info(path: &str, info: &mut HashMap<String, String>) {
+    use winreg::enums::HKEY_LOCAL_MACHINE;
+    use winreg::types::FromRegValue;
+    use winreg::RegKey;
+
+    let port_name = path.trim_start_matches(r"\\.\");
+    // SERIALCOMM maps driver devices such as `\Device\VCP0` to the COM port they expose
+    if let Ok(serialcomm) =
+        RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey(r"HARDWARE\DEVICEMAP\SERIALCOMM")
+    {
+        for (device, value) in serialcomm.enum_values().flatten() {
+            if String::from_reg_value(&value).is_ok_and(|value| value == port_name) {
+                info.insert("driver".to_string(), device);
+                break;
+            }
+        }
+    }
+}
+
+/// `ftdi_device_parameters` finds the FTDI driver parameters key of the COM port at `path`
+#[cfg(windows)]
+fn ftdi_device_parameters(path: &str, flags: u32) -> Result<winreg::RegKey, Error> {
+    use winreg::enums::HKEY_LOCAL_MACHINE;
+    use winreg::RegKey;
+
+    let port_name = path.trim_start_matches(r"\\.\");
+    let ftdibus = match RegKey::predef(HKEY_LOCAL_MACHINE).open_subkey(FTDIBUS) {
+        Ok(ftdibus) => ftdibus,
+        Err(error) => {
+            return Err(Error::String(format!(
+                "No FTDI driver installed: {}",
+                error
+            )))
+        }
+    };
+    for device in ftdibus.enum_keys().flatten() {
+        let key = format!(r"{}\0000\Device Parameters", device);
+        if let Ok(parameters) = ftdibus.open_subkey_with_flags(&key, flags) {
+            if parameters
+                .get_value::<String, _>("PortName")
+                .is_ok_and(|name| name == port_name)
+            {
+                return Ok(parameters);
+            }
+        }
+    }
+    Err(Error::String(format!(
+        "Serial port {} is not an FTDI adapter",
+        path
+    )))
+}
+
+/// `latency_timer` reads the `LatencyTimer` FTDI driver parameter
+#[cfg(windows)]
+pub fn latency_timer(path: &str) -> Result<u8, Error> {
+    use winreg::enums::KEY_READ;
+
+    match ftdi_device_parameters(path, KEY_READ)?.get_value::<u32, _>("LatencyTimer") {
+        Ok(value) => Ok(value.min(u8::MAX as u32) as u8),
+        Err(error) => Err(Error::String(format!(
+            "Failed to read the latency timer of serial port {}: {}",
+            path, error
+        ))),
+    }
+}
+
+/// `set_latency_timer` writes the `LatencyTimer` FTDI driver parameter
+#[cfg(windows)]
+pub fn set_latency_timer(path: &str, value: u8) -> Result<(), Error> {
+    use winreg::enums::{KEY_READ, KEY_SET_VALUE};
+
+    match ftdi_device_parameters(path, KEY_READ | KEY_SET_VALUE)?
+        .set_value("LatencyTimer", &(value as u32))
+    {
+        Ok(_) => Ok(()),
+        Err(error) => Err(Error::String(format!(
+            "Failed to set the latency timer of serial port {}: {}",
+            path, error
+        ))),
+    }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn driver_info(_path: &str, _info: &mut HashMap<String, String>) {}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn latency_timer(_path: &str) -> Result<u8, Error> {
+    Err(Error::String(
+        "The latency timer is not supported on this platform".to_string(),
+    ))
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+pub fn set_latency_timer(_path: &str, _value: u8) -> Result<(), Error> {
+    Err(Error::String(
+        "The latency timer is not supported on this platform".to_string(),
+    ))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn open_mark_space(
+    _builder: serialport::SerialPortBuilder,
+    _mark: bool,
+    _exclusive: bool,
+) -> serialport::Result<Box<dyn serialport::SerialPort>> {
+    Err(serialport::Error::new(
+        serialport::ErrorKind::InvalidInput,
+        "Mark and space parity are not supported on this platform",
+    ))
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+pub fn set_mark_space(_port: &serialport::TTYPort, _mark: bool) -> serialport::Result<()> {
+    Err(serialport::Error::new(
+        serialport::ErrorKind::InvalidInput,
+        "Mark and space parity are not supported on this platform",
+    ))
+}
@@ -0,0 +1,774 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::compression::Compression;
+use crate::error::Error;
+use crate::events::{
+    EventSink, BREAK_EVENT, DISCONNECTED_EVENT, ERROR_EVENT, READ_EVENT, SEQUENCE_GAP_EVENT,
+    STATS_EVENT,
+};
+use crate::framing::LengthPrefix;
+use crate::state::{
+    DisconnectEvent, FrameData, LastFrame, ReadCounters, ReadData, ReadStats, SequenceGap,
+    SerialportInfo,
+};
+use base64::prelude::{Engine, BASE64_STANDARD};
+use log::{error, info, warn};
+use serde::Serialize;
+use serialport::SerialPort;
+use std::io::ErrorKind;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Size of the buffer the reader reads into outside of raw mode
+const READ_BUFFER_SIZE: usize = 1024;
+
+/// How `read` splits the incoming stream
+#[derive(Clone, Copy, PartialEq)]
+pub enum ReadMode {
+    /// Frames end with the delimiter or follow a length header
+    Line,
+    /// Every chunk is emitted as it's received, reading up to this many bytes at a time
+    Raw(usize),
+    /// Frames of exactly this many bytes
+    Fixed(usize),
+}
+
+impl ReadMode {
+    /// `parse` converts a mode name, using `size` as the chunk or frame size
+    pub fn parse(mode: &str, size: usize) -> Result<Self, Error> {
+        if size == 0 && mode != "line" {
+            return Err(Error::String("The read size can not be zero!".to_string()));
+        }
+        match mode {
+            "line" => Ok(ReadMode::Line),
+            "raw" => Ok(ReadMode::Raw(size)),
+            "fixed" => Ok(ReadMode::Fixed(size)),
+            mode => Err(Error::String(format!(
+                "Invalid read mode {}, expected line, raw or fixed",
+                mode
+            ))),
+        }
+    }
+
+    /// `read_size` is the number of bytes the reader asks the port for at once. Frames are cut
+    /// from each chunk byte by byte, so a frame may end anywhere within it.
+    fn read_size(&self) -> usize {
+        match self {
+            ReadMode::Raw(size) => *size,
+            _ => READ_BUFFER_SIZE,
+        }
+    }
+}
+
+/// How `read` puts the frames into the emitted `ReadData`
+#[derive(Clone, Copy, PartialEq)]
+pub enum Encoding {
+    /// An array of byte values
+    Bytes,
+    /// A string of lowercase hex digits, two per byte
+    Hex,
+    /// A standard base64 string, the most compact on the IPC bridge
+    Base64,
+}
+
+impl Encoding {
+    /// `parse` converts an encoding name
+    pub fn parse(encoding: &str) -> Result<Self, Error> {
+        match encoding {
+            "bytes" => Ok(Encoding::Bytes),
+            "hex" => Ok(Encoding::Hex),
+            "base64" => Ok(Encoding::Base64),
+            encoding => Err(Error::String(format!(
+                "Invalid encoding {}, expected bytes, hex or base64",
+                encoding
+            ))),
+        }
+    }
+
+    /// `encode` prepares `frame` for a `ReadData`
+    fn encode<'a>(&self, frame: &'a [u8]) -> FrameData<'a> {
+        match self {
+            Encoding::Bytes => FrameData::Bytes(frame),
+            Encoding::Hex => {
+                FrameData::Text(frame.iter().map(|byte| format!("{:02x}", byte)).collect())
+            }
+            Encoding::Base64 => FrameData::Text(BASE64_STANDARD.encode(frame)),
+        }
+    }
+}
+
+/// How the read thread splits the incoming stream into frames
+#[derive(Clone)]
+pub struct ReaderOptions {
+    /// Framing mode, the delimiter and length header options only apply to `ReadMode::Line`
+    pub mode: ReadMode,
+    /// Byte sequence that terminates a frame
+    pub delimiter: Vec<u8>,
+    /// Keep the delimiter at the end of emitted frames
+    pub include_delimiter: bool,
+    /// Split frames by a length header in this format instead of `delimiter`, see
+    /// `Reader::push_length_prefixed`
+    pub length_prefix: Option<LengthPrefix>,
+    /// Largest payload a length header may announce
+    pub max_frame_len: usize,
+    /// Largest frame buffered while waiting for the delimiter, see `Reader::push`
+    pub max_message_size: usize,
+    /// Read timeout of the reader's clone of the port, instead of the one the port was opened with
+    pub timeout: Option<Duration>,
+    /// Emit a partial frame once its first byte has been buffered for this long
+    pub max_latency: Option<Duration>,
+    /// Emit throughput statistics at this interval
+    pub stats_interval: Option<Duration>,
+    /// How frames are put into the emitted `ReadData`
+    pub encoding: Encoding,
+    /// Include a hex dump of each frame in the emitted `ReadData`
+    pub hexdump: bool,
+    /// Emit only to the webview window with this label instead of to all targets. This is the
+    /// initial target, `set_read_target` can change it while the reader runs.
+    pub target_label: Option<String>,
+    /// Treat NUL bytes as a break condition, see `Reader::push`
+    pub detect_break: bool,
+    /// Decompress each frame before emitting it, up to `max_message_size` bytes
+    pub decompress: Option<Compression>,
+    /// Keep a copy of the most recent frame for `get_last_frame`
+    pub retain_last_frame: bool,
+    /// Byte values dropped from the stream before anything else sees them
+    pub strip_bytes: Vec<u8>,
+    /// Offset and length in bytes of a big-endian sequence number in each frame, see
+    /// `Reader::check_sequence`
+    pub sequence_field: Option<(usize, usize)>,
+}
+
+impl ReaderOptions {
+    /// `poll_interval` is the longest the reader may block before it has to check its timers
+    fn poll_interval(&self) -> Option<Duration> {
+        match (self.max_latency, self.stats_interval) {
+            (Some(max_latency), Some(stats_interval)) => Some(max_latency.min(stats_interval)),
+            (max_latency, stats_interval) => max_latency.or(stats_interval),
+        }
+    }
+
+    /// `read_timeout` is the read timeout of the reader's clone of a port whose own timeout is
+    /// `port_timeout`: `timeout` if given, capped by the poll interval, as the read timeout bounds
+    /// how late the reader's timers can fire
+    pub fn read_timeout(&self, port_timeout: Duration) -> Duration {
+        let timeout = self.timeout.unwrap_or(port_timeout);
+        match self.poll_interval() {
+            Some(poll_interval) => timeout.min(poll_interval),
+            None => timeout,
+        }
+    }
+
+    /// `apply_timeout` sets the read timeout of `serial`, the reader's clone of the port, as
+    /// `read_timeout` describes
+    fn apply_timeout(&self, serial: &mut dyn SerialPort) {
+        let timeout = self.read_timeout(serial.timeout());
+        if timeout != serial.timeout() {
+            if let Err(error) = serial.set_timeout(timeout) {
+                warn!("Failed to set read timeout: {}", error);
+            }
+        }
+    }
+}
+
+/// State of the read thread
+struct Reader<S: EventSink> {
+    sink: S,
+    path: String,
+    options: ReaderOptions,
+    // Buffer to store the message
+    message_buf: Vec<u8>,
+    // Length header being received and the payload length it announced, with `length_prefix`
+    header_buf: Vec<u8>,
+    frame_len: Option<usize>,
+    // Length of `message_buf`, shared with `read_buffer_len`
+    buffered: Arc<AtomicUsize>,
+    // Label of the window to emit to, shared with `set_read_target`
+    read_target: Arc<Mutex<Option<String>>>,
+    // Copy of the last emitted frame, shared with `get_last_frame`
+    last_frame: Arc<Mutex<Option<LastFrame>>>,
+    // When the first byte of the current message was buffered
+    first_byte_at: Option<Instant>,
+    // Whether the current frame was cut at `max_message_size`
+    truncated: bool,
+    // Whether the previous byte was part of a break
+    in_break: bool,
+    // Sequence number of the previous frame
+    last_sequence: Option<u64>,
+    // Counters since the last stats event
+    bytes_read: usize,
+    frames_emitted: usize,
+    stats_since: Instant,
+    // Totals since the reader started, shared with `read_stats`
+    counters: Arc<ReadCounters>,
+}
+
+impl<S: EventSink> Reader<S> {
+    /// `new` prepares the framing state of a reader of the port of `serialport_info`, sharing its
+    /// buffer length, target, last frame and counters
+    fn new(
+        sink: S,
+        path: String,
+        options: ReaderOptions,
+        serialport_info: &SerialportInfo,
+    ) -> Self {
+        Reader {
+            sink,
+            path,
+            options,
+            message_buf: Vec::new(),
+            header_buf: Vec::new(),
+            frame_len: None,
+            buffered: serialport_info.buffered.clone(),
+            read_target: serialport_info.read_target.clone(),
+            last_frame: serialport_info.last_frame.clone(),
+            first_byte_at: None,
+            truncated: false,
+            in_break: false,
+            last_sequence: None,
+            bytes_read: 0,
+            frames_emitted: 0,
+            stats_since: Instant::now(),
+            counters: serialport_info.read_counters.clone(),
+        }
+    }
+
+    fn emit<T: Serialize + Clone>(&self, event_type: &'static str, payload: T) {
+        let target_label = match self.read_target.lock() {
+            Ok(read_target) => read_target.clone(),
+            Err(_) => None,
+        };
+        self.sink
+            .emit_port_event(target_label.as_deref(), event_type, &self.path, payload);
+    }
+
+    /// `emit_error` emits an `error` event, counting it for `read_stats`
+    fn emit_error(&self, message: String) {
+        self.counters.read_errors.fetch_add(1, Ordering::Relaxed);
+        self.emit(ERROR_EVENT, message);
+    }
+
+    /// `push` adds a received byte to the current frame.
+    ///
+    /// A frame that reaches `max_message_size` without a delimiter is emitted as it is, flagged as
+    /// truncated, and the rest of the message starts a new frame.
+    ///
+    /// Bytes listed in `strip_bytes` are dropped first, so they can't split or end a frame.
+    ///
+    /// With `detect_break`, NUL bytes are taken as a break condition instead of data: in the raw
+    /// mode `serialport` configures, termios delivers a break (a framing error with all-zero data)
+    /// as a single `0x00`. A run of NUL bytes, as seen during a long break, emits one `break`
+    /// event. This is a heuristic: it works on Linux and macOS, is best effort on Windows, and
+    /// can't tell a break from a genuine `0x00` data byte.
+    fn push(&mut self, byte: u8) {
+        if self.options.strip_bytes.contains(&byte) {
+            return;
+        }
+        if self.options.detect_break {
+            if byte == 0 {
+                if !self.in_break {
+                    self.in_break = true;
+                    self.emit(
+                        BREAK_EVENT,
+                        format!("Break detected on serial port {}", &self.path),
+                    );
+                }
+                return;
+            }
+            self.in_break = false;
+        }
+        self.bytes_read += 1;
+        self.counters.bytes_read.fetch_add(1, Ordering::Relaxed);
+        match self.options.mode {
+            ReadMode::Line => {}
+            ReadMode::Raw(_) => {
+                // Emitted by `end_chunk` once the whole chunk is buffered
+                self.buffer(byte);
+                return;
+            }
+            ReadMode::Fixed(size) => {
+                self.buffer(byte);
+                if self.message_buf.len() == size {
+                    self.emit_frame();
+                }
+                return;
+            }
+        }
+        if let Some(length_prefix) = self.options.length_prefix {
+            self.push_length_prefixed(length_prefix, byte);
+            return;
+        }
+        self.buffer(byte);
+
+        // Check if the delimiter is encountered, indicating the end of a message
+        if self.message_buf.ends_with(&self.options.delimiter) {
+            if !self.options.include_delimiter {
+                let len = self.message_buf.len() - self.options.delimiter.len();
+                self.message_buf.truncate(len);
+            }
+            self.emit_frame();
+        } else if self.message_buf.len() >= self.options.max_message_size {
+            // A device that never sends the delimiter must not grow the buffer without bound
+            self.truncated = true;
+            self.emit_frame();
+        }
+    }
+
+    /// `push_length_prefixed` collects a length header and then exactly the number of payload
+    /// bytes it announces, emitting the payload as one frame. A header announcing more than
+    /// `max_frame_len` bytes is reported as an error and dropped, and framing restarts with the
+    /// next byte.
+    fn push_length_prefixed(&mut self, length_prefix: LengthPrefix, byte: u8) {
+        match self.frame_len {
+            None => {
+                self.header_buf.push(byte);
+                if self.header_buf.len() < length_prefix.size() {
+                    return;
+                }
+                let frame_len = length_prefix.decode(&self.header_buf);
+                self.header_buf.clear();
+                if frame_len > self.options.max_frame_len {
+                    self.emit_error(format!(
+                        "Frame of {} bytes on serial port {} exceeds the maximum of {} bytes",
+                        frame_len, &self.path, self.options.max_frame_len
+                    ));
+                    return;
+                }
+                self.frame_len = Some(frame_len);
+            }
+            Some(_) => self.buffer(byte),
+        }
+        if self.frame_len == Some(self.message_buf.len()) {
+            self.emit_frame();
+        }
+    }
+
+    /// `push_chunk` frames the bytes delivered by one read. Frames may start and end anywhere in
+    /// it, the bytes of an unfinished frame are kept for the next chunk.
+    fn push_chunk(&mut self, chunk: &[u8]) {
+        for &byte in chunk {
+            self.push(byte);
+        }
+        self.end_chunk();
+    }
+
+    /// `end_chunk` emits what was buffered from one read in raw mode. Raw mode never holds bytes
+    /// back waiting for a delimiter, so the buffer is empty again afterwards.
+    fn end_chunk(&mut self) {
+        if matches!(self.options.mode, ReadMode::Raw(_)) && !self.message_buf.is_empty() {
+            self.emit_frame();
+        }
+    }
+
+    /// `buffer` appends a byte to the current frame
+    fn buffer(&mut self, byte: u8) {
+        if self.message_buf.is_empty() {
+            self.first_byte_at = Some(Instant::now());
+        }
+        self.message_buf.push(byte);
+        self.buffered
+            .store(self.message_buf.len(), Ordering::Relaxed);
+    }
+
+    /// `check_sequence` emits a `sequence-gap` event when the sequence number of `frame` doesn't
+    /// follow the previous one. Sequence numbers wrap around at the field's width, and frames too
+    /// short to hold the field are skipped.
+    fn check_sequence(&mut self, frame: &[u8]) {
+        let (offset, length) = match self.options.sequence_field {
+            Some(field) => field,
+            None => return,
+        };
+        let field = match frame.get(offset..offset + length) {
+            Some(field) => field,
+            None => return,
+        };
+        let received = field
+            .iter()
+            .fold(0u64, |sequence, &byte| (sequence << 8) | byte as u64);
+        if let Some(last) = self.last_sequence {
+            let mask = u64::MAX >> (64 - 8 * length);
+            let expected = last.wrapping_add(1) & mask;
+            if received != expected {
+                self.emit(SEQUENCE_GAP_EVENT, SequenceGap { expected, received });
+            }
+        }
+        self.last_sequence = Some(received);
+    }
+
+    /// `emit_frame` emits the buffered message to the frontend and clears the buffer. With
+    /// `decompress`, a frame that fails to decompress is reported as an error instead.
+    fn emit_frame(&mut self) {
+        let mut frame = std::mem::take(&mut self.message_buf);
+        if let Some(compression) = self.options.decompress {
+            match compression.decompress(&frame, self.options.max_message_size) {
+                Ok(decompressed) => frame = decompressed,
+                Err(error) => {
+                    self.emit_error(format!(
+                        "Failed to decompress a frame from serial port {}: {}",
+                        &self.path, error
+                    ));
+                    self.reset_frame(frame);
+                    return;
+                }
+            }
+        }
+        self.check_sequence(&frame);
+        self.emit(
+            READ_EVENT,
+            ReadData {
+                data: self.options.encoding.encode(&frame),
+                size: frame.len(),
+                hexdump: self.options.hexdump.then(|| hexdump(&frame)),
+                truncated: self.truncated,
+            },
+        );
+        if self.options.retain_last_frame {
+            if let Ok(mut last_frame) = self.last_frame.lock() {
+                *last_frame = Some(LastFrame {
+                    data: frame.clone(),
+                    size: frame.len(),
+                });
+            }
+        }
+        self.frames_emitted += 1;
+        self.counters.frames_emitted.fetch_add(1, Ordering::Relaxed);
+        self.reset_frame(frame);
+    }
+
+    /// `reset_frame` starts the next frame, reusing the allocation of `frame`
+    fn reset_frame(&mut self, mut frame: Vec<u8>) {
+        frame.clear();
+        self.message_buf = frame;
+        self.frame_len = None;
+        self.buffered.store(0, Ordering::Relaxed);
+        self.first_byte_at = None;
+        self.truncated = false;
+    }
+
+    /// `check_timers` flushes a stale partial message and emits due throughput statistics
+    fn check_timers(&mut self) {
+        // Flush a partial message that has been waiting for its delimiter for too long. A
+        // length-prefixed or fixed-size frame is never split, as the rest of it would be taken
+        // for the start of the next one.
+        if let (Some(max_latency), Some(since), None, ReadMode::Line) = (
+            self.options.max_latency,
+            self.first_byte_at,
+            self.options.length_prefix,
+            self.options.mode,
+        ) {
+            if since.elapsed() >= max_latency {
+                self.emit_frame();
+            }
+        }
+
+        if let Some(stats_interval) = self.options.stats_interval {
+            let elapsed = self.stats_since.elapsed();
+            if elapsed >= stats_interval {
+                self.emit(
+                    STATS_EVENT,
+                    ReadStats {
+                        bytes: self.bytes_read,
+                        frames: self.frames_emitted,
+                        interval_ms: elapsed.as_millis() as u64,
+                        bytes_per_second: self.bytes_read as f64 / elapsed.as_secs_f64(),
+                    },
+                );
+                self.bytes_read = 0;
+                self.frames_emitted = 0;
+                self.stats_since = Instant::now();
+            }
+        }
+    }
+}
+
+/// `hexdump` formats `data` as a classic dump, 16 bytes per line:
+/// `0000  48 65 6c 6c 6f                                   |Hello|`
+pub fn hexdump(data: &[u8]) -> String {
+    let mut dump = String::new();
+    for (line, chunk) in data.chunks(16).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|byte| format!("{:02x}", byte)).collect();
+        let ascii: String = chunk
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        if line > 0 {
+            dump.push('\n');
+        }
+        dump.push_str(&format!(
+            "{:04x}  {:<47}  |{}|",
+            line * 16,
+            hex.join(" "),
+            ascii
+        ));
+    }
+    dump
+}
+
+/// `spawn_reader` starts the thread that reads `serial`, a clone of the port of `serialport_info`,
+/// and emits framed data until a stop signal is received on `rx` or the channel is disconnected.
+/// `on_error` is called when the thread exits because the port failed. Events go to `sink`.
+pub fn spawn_reader<S: EventSink>(
+    sink: S,
+    path: String,
+    mut serial: Box<dyn SerialPort>,
+    rx: Receiver<usize>,
+    serialport_info: &SerialportInfo,
+    options: ReaderOptions,
+    on_error: Option<Box<dyn FnOnce() + Send>>,
+) -> JoinHandle<()> {
+    options.apply_timeout(serial.as_mut());
+    let last_activity = serialport_info.last_activity.clone();
+    if let Ok(mut read_target) = serialport_info.read_target.lock() {
+        *read_target = options.target_label.clone();
+    }
+    let mut reader = Reader::new(sink, path, options, serialport_info);
+    let mut buf = vec![0; reader.options.mode.read_size()];
+    thread::spawn(move || {
+        loop {
+            // Check if a signal has been received to stop reading
+            match rx.try_recv() {
+                Ok(_) | Err(TryRecvError::Disconnected) => {
+                    // If a signal is received or the channel is disconnected, break the loop and exit
+                    info!("Received stop signal for serial port {}", &reader.path);
+                    break;
+                }
+                _ => {} // Continue reading data if no signal received
+            }
+            match serial.read(&mut buf) {
+                Ok(0) => {}
+                Ok(count) => {
+                    if let Ok(mut last_activity) = last_activity.lock() {
+                        *last_activity = Instant::now();
+                    }
+                    reader.push_chunk(&buf[..count]);
+                }
+                Err(ref err) if err.kind() == ErrorKind::TimedOut => {
+                    // Timed out, continue waiting for data
+                }
+                Err(err) => {
+                    error!("Failed to read from serial port: {:?}", err);
+                    reader.emit_error(format!("Failed to read from serial port: {}", err));
+                    // A failing read usually means the device was removed
+                    reader.emit(
+                        DISCONNECTED_EVENT,
+                        DisconnectEvent {
+                            path: reader.path.clone(),
+                            reason: err.to_string(),
+                            kind: "read",
+                        },
+                    );
+                    if let Some(on_error) = on_error {
+                        on_error();
+                    }
+                    break; // Break out of the loop for other errors
+                }
+            }
+            reader.check_timers();
+        }
+        // The partial frame is dropped along with the reader
+        reader.buffered.store(0, Ordering::Relaxed);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{line_options, MockPort, Recorder};
+
+    /// `reader` returns a reader emitting to a fresh `Recorder`
+    fn reader(options: ReaderOptions) -> (Reader<Recorder>, Recorder) {
+        let recorder = Recorder::default();
+        let serialport_info = SerialportInfo::new(Box::new(MockPort::new()));
+        let reader = Reader::new(
+            recorder.clone(),
+            "mock".to_string(),
+            options,
+            &serialport_info,
+        );
+        (reader, recorder)
+    }
+
+    #[test]
+    fn apply_timeout_overrides_the_port_timeout() {
+        let mut port = MockPort::new();
+        port.set_timeout(Duration::from_millis(200)).unwrap();
+        let mut serial = port.try_clone().unwrap();
+        let options = ReaderOptions {
+            timeout: Some(Duration::from_millis(1000)),
+            ..line_options()
+        };
+        options.apply_timeout(serial.as_mut());
+        assert_eq!(serial.timeout(), Duration::from_millis(1000));
+        // Only the reader's clone is changed
+        assert_eq!(port.timeout(), Duration::from_millis(200));
+    }
+
+    #[test]
+    fn apply_timeout_keeps_the_port_timeout_by_default() {
+        let mut serial = MockPort::new();
+        serial.set_timeout(Duration::from_millis(200)).unwrap();
+        line_options().apply_timeout(&mut serial);
+        assert_eq!(serial.timeout(), Duration::from_millis(200));
+    }
+
+    #[test]
+    fn read_timeout_prefers_the_reader_timeout() {
+        let port_timeout = Duration::from_millis(200);
+        assert_eq!(line_options().read_timeout(port_timeout), port_timeout);
+        let options = ReaderOptions {
+            timeout: Some(Duration::from_secs(5)),
+            ..line_options()
+        };
+        assert_eq!(options.read_timeout(port_timeout), Duration::from_secs(5));
+    }
+
+    #[test]
+    fn apply_timeout_is_capped_by_the_poll_interval() {
+        let mut serial = MockPort::new();
+        let options = ReaderOptions {
+            timeout: Some(Duration::from_millis(1000)),
+            max_latency: Some(Duration::from_millis(50)),
+            ..line_options()
+        };
+        options.apply_timeout(&mut serial);
+        assert_eq!(serial.timeout(), Duration::from_millis(50));
+    }
+
+    #[test]
+    fn hexdump_formats_lines_of_sixteen_bytes() {
+        assert_eq!(
+            hexdump(b"Hello"),
+            format!("0000  {:<47}  |Hello|", "48 65 6c 6c 6f")
+        );
+        let dump = hexdump(b"0123456789abcdef\x00\x7f");
+        let lines: Vec<&str> = dump.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("|0123456789abcdef|"));
+        assert_eq!(lines[1], format!("0010  {:<47}  |..|", "00 7f"));
+        assert_eq!(hexdump(b""), "");
+    }
+
+    #[test]
+    fn read_mode_parse_checks_the_size() {
+        assert!(ReadMode::parse("line", 0).unwrap() == ReadMode::Line);
+        assert!(ReadMode::parse("raw", 64).unwrap() == ReadMode::Raw(64));
+        assert!(ReadMode::parse("fixed", 8).unwrap() == ReadMode::Fixed(8));
+        assert!(ReadMode::parse("raw", 0).is_err());
+        assert!(ReadMode::parse("lines", 8).is_err());
+    }
+
+    #[test]
+    fn read_size_reads_in_bulk_outside_of_raw_mode() {
+        assert_eq!(ReadMode::Line.read_size(), READ_BUFFER_SIZE);
+        assert_eq!(ReadMode::Fixed(4).read_size(), READ_BUFFER_SIZE);
+        assert_eq!(ReadMode::Raw(64).read_size(), 64);
+    }
+
+    #[test]
+    fn frames_span_reads() {
+        let (mut reader, recorder) = reader(line_options());
+        reader.push_chunk(b"first\nsec");
+        reader.push_chunk(b"ond\nthi");
+        reader.push_chunk(b"rd");
+        reader.push_chunk(b"\n");
+        assert_eq!(
+            recorder.frames(),
+            vec![
+                b"first\n".to_vec(),
+                b"second\n".to_vec(),
+                b"third\n".to_vec()
+            ]
+        );
+    }
+
+    #[test]
+    fn delimiter_split_across_reads_loses_no_bytes() {
+        let (mut reader, recorder) = reader(ReaderOptions {
+            delimiter: b"\r\n".to_vec(),
+            include_delimiter: false,
+            ..line_options()
+        });
+        reader.push_chunk(b"one\r");
+        reader.push_chunk(b"\ntwo\r");
+        reader.push_chunk(b"\n");
+        assert_eq!(recorder.frames(), vec![b"one".to_vec(), b"two".to_vec()]);
+        assert_eq!(reader.message_buf, b"");
+    }
+
+    #[test]
+    fn multi_byte_delimiter_matches_across_pushes() {
+        let (mut reader, recorder) = reader(ReaderOptions {
+            delimiter: vec![0x1b, b'E', b'\n'],
+            ..line_options()
+        });
+        // A prefix of the delimiter that doesn't complete it stays part of the frame
+        reader.push_chunk(b"a\x1b");
+        reader.push_chunk(b"Eb\x1b");
+        reader.push_chunk(b"\x1bE");
+        reader.push_chunk(b"\nc");
+        assert_eq!(recorder.frames(), vec![b"a\x1bEb\x1b\x1bE\n".to_vec()]);
+        assert_eq!(reader.message_buf, b"c");
+    }
+
+    #[test]
+    fn multi_byte_delimiter_can_be_stripped() {
+        let (mut reader, recorder) = reader(ReaderOptions {
+            delimiter: b"\r\n".to_vec(),
+            include_delimiter: false,
+            ..line_options()
+        });
+        for &byte in b"x\r\ry\r\n" {
+            reader.push_chunk(&[byte]);
+        }
+        assert_eq!(recorder.frames(), vec![b"x\r\ry".to_vec()]);
+    }
+
+    #[test]
+    fn unfinished_frame_is_kept_for_the_next_read() {
+        let (mut reader, recorder) = reader(line_options());
+        reader.push_chunk(b"partial");
+        assert!(recorder.frames().is_empty());
+        assert_eq!(reader.buffered.load(Ordering::Relaxed), 7);
+        reader.push_chunk(b" frame\n");
+        assert_eq!(recorder.frames(), vec![b"partial frame\n".to_vec()]);
+    }
+
+    #[test]
+    fn raw_mode_emits_each_read() {
+        let (mut reader, recorder) = reader(ReaderOptions {
+            mode: ReadMode::Raw(64),
+            ..line_options()
+        });
+        reader.push_chunk(b"ab\n");
+        reader.push_chunk(b"c");
+        assert_eq!(recorder.frames(), vec![b"ab\n".to_vec(), b"c".to_vec()]);
+    }
+
+    #[test]
+    fn fixed_frames_span_reads() {
+        let (mut reader, recorder) = reader(ReaderOptions {
+            mode: ReadMode::Fixed(4),
+            ..line_options()
+        });
+        reader.push_chunk(b"abcdef");
+        reader.push_chunk(b"gh");
+        assert_eq!(recorder.frames(), vec![b"abcd".to_vec(), b"efgh".to_vec()]);
+    }
+
+    #[test]
+    fn encoding_parse_rejects_unknown_names() {
+        assert!(Encoding::parse("hex").unwrap() == Encoding::Hex);
+        assert!(Encoding::parse("utf8").is_err());
+    }
+}
@@ -1,18 +1,179 @@
-use serde::Serialize;
+use crate::reader::ReaderOptions;
+use crate::writer::WriteJob;
+use serde::{Deserialize, Serialize};
 use serialport::{self, SerialPort};
 use std::{
     collections::HashMap,
-    sync::{mpsc::Sender, Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        mpsc::Sender,
+        Arc, Mutex,
+    },
+    thread::JoinHandle,
+    time::{Duration, Instant},
 };
 
 #[derive(Default)]
 pub struct SerialportState {
     // plugin state, configuration fields
     pub serialports: Arc<Mutex<HashMap<String, SerialportInfo>>>,
+    pub defaults: SerialPortOptions,
+    // last port enumeration with and without `include_all` and when it was taken, for
+    // `available_ports_cached`
+    pub ports_cache: Mutex<HashMap<bool, (Instant, PortList)>>,
+    // status of the writes queued by `write_async`, until `write_status` reports them finished,
+    // keeping only the latest finished writes
+    pub writes: Arc<Mutex<HashMap<u64, WriteStatus>>>,
+    pub next_write_id: AtomicU64,
+    // stops the hot-plug scanner started by `start_port_scan`
+    pub port_scan: Mutex<Option<Sender<usize>>>,
 }
+
+/// Ports by name, each with the details returned by `available_ports`
+pub type PortList = HashMap<String, HashMap<String, String>>;
+
+/// Kind of a listed port, see `PortInfo`
+#[derive(Serialize, Clone, Copy, Debug, PartialEq)]
+pub enum PortType {
+    #[serde(rename = "USB")]
+    Usb,
+    Bluetooth,
+    #[serde(rename = "PCI")]
+    Pci,
+    Unknown,
+}
+
+/// Details of a port returned by `available_ports_detailed`, with missing values left out instead
+/// of reported as `Unknown`
+#[derive(Serialize, Clone, Debug)]
+pub struct PortInfo {
+    pub port_type: PortType,
+    pub vid: Option<u16>,
+    pub pid: Option<u16>,
+    pub serial_number: Option<String>,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+}
+
+/// Default options applied to every `open` call. Options passed to `open` take precedence, and
+/// anything left unset here falls back to the built-in defaults.
+#[derive(Default, Clone, Debug, Deserialize)]
+pub struct SerialPortOptions {
+    pub data_bits: Option<usize>,
+    pub flow_control: Option<String>,
+    pub parity: Option<String>,
+    pub stop_bits: Option<usize>,
+    pub timeout: Option<u64>,
+}
+
+/// Backoff between the attempts to reopen a port that was lost while reading. The delay starts
+/// at `initial_ms` (500 ms by default) and is multiplied by `multiplier` (2 by default) after
+/// every failed attempt, up to `max_ms` (30 s by default). Without `max_attempts` the attempts go
+/// on until the port is reopened, closed or opened again.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ReconnectPolicy {
+    pub initial_ms: Option<u64>,
+    pub max_ms: Option<u64>,
+    pub multiplier: Option<f64>,
+    pub max_attempts: Option<u32>,
+}
+
+/// A step of the control line sequence `open` performs on a newly opened port: `line` (`dtr` or
+/// `rts`) is set to `level`, then the sequence waits for `delay_ms`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct SignalStep {
+    pub line: String,
+    pub level: bool,
+    pub delay_ms: u64,
+}
+
+/// The connection handshake `connect_device` performs: the port is opened with the given settings,
+/// `signals` are applied, `init` is written and a response matching `expect` is awaited for up to
+/// `response_timeout_ms` (1 s by default).
+#[derive(Clone, Debug, Deserialize)]
+pub struct DeviceConfig {
+    pub baud_rate: u32,
+    pub data_bits: Option<usize>,
+    pub flow_control: Option<String>,
+    pub parity: Option<String>,
+    pub stop_bits: Option<usize>,
+    pub timeout: Option<u64>,
+    pub signals: Option<Vec<SignalStep>>,
+    pub init: Option<Vec<u8>>,
+    pub expect: Option<String>,
+    pub response_timeout_ms: Option<u64>,
+}
+
 pub struct SerialportInfo {
     pub serialport: Box<dyn SerialPort>,
     pub sender: Option<Sender<usize>>,
+    pub reader: Option<JoinHandle<()>>,
+    // time of the last read or write, shared with the reader and auto-close threads
+    pub last_activity: Arc<Mutex<Instant>>,
+    pub auto_close_sender: Option<Sender<usize>>,
+    // set once the port is closed, so blocking reads on a clone of it can bail out
+    pub closing: Arc<AtomicBool>,
+    // bytes the reader has buffered toward its next frame
+    pub buffered: Arc<AtomicUsize>,
+    // label of the window the reader emits to, all targets when unset; see `set_read_target`
+    pub read_target: Arc<Mutex<Option<String>>>,
+    // most recent frame of a reader started with `retain_last_frame`
+    pub last_frame: Arc<Mutex<Option<LastFrame>>>,
+    // settings the port was opened with, used by `reconnect`; unset for ports opened from an fd
+    pub open_settings: Option<PortSettings>,
+    pub auto_close_after: Option<Duration>,
+    pub reconnect_policy: Option<ReconnectPolicy>,
+    // control line sequence performed after opening, replayed when reconnecting
+    pub open_sequence: Vec<SignalStep>,
+    // false for ports opened in shared mode, kept when reconnecting
+    pub exclusive: bool,
+    // raise RTS around writes for RS485 transceivers, shared with the writer thread
+    pub rs485: Arc<AtomicBool>,
+    // totals of the reader since the last `read`, shared with the read thread
+    pub read_counters: Arc<ReadCounters>,
+    // options of the last started reader, so `reconnect` can restart it
+    pub reader_options: Option<ReaderOptions>,
+    // queue of the writer thread started by the first `write_async`
+    pub writer: Option<Sender<WriteJob>>,
+}
+
+impl SerialportInfo {
+    pub fn new(serialport: Box<dyn SerialPort>) -> Self {
+        SerialportInfo {
+            serialport,
+            sender: None,
+            reader: None,
+            last_activity: Arc::new(Mutex::new(Instant::now())),
+            auto_close_sender: None,
+            closing: Arc::new(AtomicBool::new(false)),
+            buffered: Arc::new(AtomicUsize::new(0)),
+            read_target: Arc::new(Mutex::new(None)),
+            last_frame: Arc::new(Mutex::new(None)),
+            open_settings: None,
+            auto_close_after: None,
+            reconnect_policy: None,
+            open_sequence: Vec::new(),
+            exclusive: true,
+            rs485: Arc::new(AtomicBool::new(false)),
+            read_counters: Arc::new(ReadCounters::default()),
+            reader_options: None,
+            writer: None,
+        }
+    }
+
+    /// `touch` records read or write activity, resetting the auto-close timer
+    pub fn touch(&self) {
+        if let Ok(mut last_activity) = self.last_activity.lock() {
+            *last_activity = Instant::now();
+        }
+    }
+}
+
+impl Drop for SerialportInfo {
+    fn drop(&mut self) {
+        // However the port gets closed, interrupt the blocking reads still using it
+        self.closing.store(true, Ordering::SeqCst);
+    }
 }
 
 #[derive(Serialize, Clone)]
@@ -21,8 +182,210 @@ pub struct InvokeResult {
     pub message: String,
 }
 
+/// Payload shared by every event the plugin emits.
+///
+/// `event_type` is the kind of event (`read`, `disconnected`, `error`, `auto-closed`, `stats`,
+/// `break`, `sequence-gap`, `write-complete`, `reconnecting`, `reconnected`, `reconnect-failed`),
+/// `path` is the port it concerns, and `payload` carries the event specific data: `ReadData` for
+/// `read` events, `ReadStats` for `stats` events, `DisconnectEvent` for `disconnected` events,
+/// `SequenceGap` for `sequence-gap` events, `WriteResult` for `write-complete` events,
+/// `ReconnectAttempt` for `reconnecting` events, `ReconnectInfo` for `reconnected` events,
+/// `PortInfo` for `port-added` and `port-removed` events and a message string for the others.
+#[derive(Serialize, Clone)]
+pub struct PortEvent<T: Serialize + Clone> {
+    pub path: String,
+    pub event_type: &'static str,
+    pub payload: T,
+}
+
+/// The settings of an open port, using the same values `open` accepts
+#[derive(Serialize, Clone)]
+pub struct PortSettings {
+    pub baud_rate: u32,
+    pub data_bits: usize,
+    pub flow_control: Option<String>,
+    pub parity: Option<String>,
+    pub stop_bits: usize,
+    pub timeout_ms: u64,
+}
+
+/// Result of `reconnect`
+#[derive(Serialize, Clone)]
+pub struct ReconnectInfo {
+    pub path: String,
+    pub settings: PortSettings,
+}
+
+/// Payload of the `reconnecting` event emitted before each attempt of an automatic reconnection
+#[derive(Serialize, Clone)]
+pub struct ReconnectAttempt {
+    pub attempt: u32,
+    pub delay_ms: u64,
+}
+
+/// Bytes received in one read during `record_traffic`, `offset_ms` after the recording started
+#[derive(Serialize, Clone)]
+pub struct TrafficChunk {
+    pub offset_ms: u64,
+    pub data: Vec<u8>,
+}
+
+/// Result of `connect_device`
+#[derive(Serialize, Clone)]
+pub struct ConnectInfo {
+    pub settings: PortSettings,
+    /// What the device answered to the init command, empty without `expect`
+    pub response: Vec<u8>,
+}
+
+/// Levels of the input control lines, returned by `read_control_signals`
+#[derive(Serialize, Clone)]
+pub struct ControlSignals {
+    pub cts: bool,
+    pub dsr: bool,
+    pub carrier_detect: bool,
+    pub ring_indicator: bool,
+}
+
+/// Result of `write_and_read`
+#[derive(Serialize, Clone)]
+pub struct WriteAndRead {
+    /// Number of bytes written
+    pub written: usize,
+    pub response: Vec<u8>,
+}
+
+/// Round-trip latency samples collected by `ping_device`, in microseconds
+#[derive(Serialize, Clone)]
+pub struct PingStats {
+    pub samples: usize,
+    pub min_us: u64,
+    pub avg_us: u64,
+    pub max_us: u64,
+}
+
+/// Counters the read thread of a port keeps up to date, see `read_stats`
+#[derive(Default)]
+pub struct ReadCounters {
+    pub bytes_read: AtomicU64,
+    pub frames_emitted: AtomicU64,
+    pub read_errors: AtomicU64,
+}
+
+impl ReadCounters {
+    /// `reset` starts counting from zero again
+    pub fn reset(&self) {
+        self.bytes_read.store(0, Ordering::Relaxed);
+        self.frames_emitted.store(0, Ordering::Relaxed);
+        self.read_errors.store(0, Ordering::Relaxed);
+    }
+
+    /// `totals` takes a snapshot of the counters
+    pub fn totals(&self) -> ReadTotals {
+        ReadTotals {
+            bytes_read: self.bytes_read.load(Ordering::Relaxed),
+            frames_emitted: self.frames_emitted.load(Ordering::Relaxed),
+            read_errors: self.read_errors.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Result of `read_stats`
+#[derive(Serialize, Clone)]
+pub struct ReadTotals {
+    pub bytes_read: u64,
+    pub frames_emitted: u64,
+    pub read_errors: u64,
+}
+
+/// Throughput of a reader since its previous `stats` event
+#[derive(Serialize, Clone)]
+pub struct ReadStats {
+    pub bytes: usize,
+    pub frames: usize,
+    pub interval_ms: u64,
+    pub bytes_per_second: f64,
+}
+
+/// Payload of `disconnected` events
+#[derive(Serialize, Clone)]
+pub struct DisconnectEvent {
+    pub path: String,
+    pub reason: String,
+    /// What failed: `read`, `write` or `clone`
+    pub kind: &'static str,
+}
+
+/// A frame whose sequence number doesn't follow the previous frame's
+#[derive(Serialize, Clone)]
+pub struct SequenceGap {
+    pub expected: u64,
+    pub received: u64,
+}
+
+/// Progress of a write queued by `write_async`
+#[derive(Serialize, Clone)]
+pub enum WriteStatus {
+    Pending,
+    Done(usize),
+    Failed(String),
+}
+
+/// Payload of the `write-complete` event
+#[derive(Serialize, Clone)]
+pub struct WriteResult {
+    pub write_id: u64,
+    pub status: WriteStatus,
+}
+
+/// Capabilities of this build reported by `runtime_info`
+#[derive(Serialize, Clone)]
+pub struct RuntimeInfo {
+    pub version: &'static str,
+    /// Whether commands can run on the async runtime instead of the main thread
+    pub async_commands: bool,
+    /// Whether readers, writers and timers can run on background threads
+    pub background_tasks: bool,
+    /// Whether `read` can decompress frames, i.e. the `decompress` feature is enabled
+    pub decompress: bool,
+    /// Whether `get_latency_timer` and `set_latency_timer` are supported on this platform
+    pub latency_timer: bool,
+    /// Whether mark and space parity are supported on this platform
+    pub mark_space_parity: bool,
+}
+
+/// Chunk size suggested by `recommended_chunk_size`
+#[derive(Serialize, Clone)]
+pub struct ChunkSizeHint {
+    pub platform: &'static str,
+    /// How payloads reach the frontend; the plugin currently always uses events
+    pub transport: &'static str,
+    pub chunk_size: usize,
+}
+
+/// The most recent frame emitted by a reader, returned by `get_last_frame`
+#[derive(Serialize, Clone)]
+pub struct LastFrame {
+    pub data: Vec<u8>,
+    pub size: usize,
+}
+
+/// A frame as emitted in `ReadData`, either raw or encoded as text as `read`'s `encoding` asks
+#[derive(Serialize, Clone)]
+#[serde(untagged)]
+pub enum FrameData<'a> {
+    Bytes(&'a [u8]),
+    Text(String),
+}
+
 #[derive(Serialize, Clone)]
 pub struct ReadData<'a> {
-    pub data: &'a [u8],
+    pub data: FrameData<'a>,
     pub size: usize,
+    /// `data` formatted as an offset/hex/ascii dump, when requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hexdump: Option<String>,
+    /// Set when the frame was cut at the maximum message size before its delimiter arrived
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
 }
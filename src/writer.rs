@@ -0,0 +1,171 @@
+// Copyright 2019-2023 Tauri Programme within The Commons Conservancy
+// SPDX-License-Identifier: Apache-2.0
+// SPDX-License-Identifier: MIT
+
+use crate::error::Error;
+use crate::events::{emit_event, WRITE_COMPLETE_EVENT};
+use crate::state::{WriteResult, WriteStatus};
+use serialport::SerialPort;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Instant;
+use tauri::{Runtime, Window};
+
+/// Most finished writes whose status is kept for `write_status`, so statuses that are never polled
+/// don't accumulate
+const MAX_FINISHED_WRITES: usize = 1024;
+
+/// A payload queued by `write_async`
+pub struct WriteJob {
+    pub write_id: u64,
+    pub data: Vec<u8>,
+}
+
+/// The port state a writer thread shares with the commands
+pub struct WriterState {
+    /// Updated after each successful write, for the auto-close timer
+    pub last_activity: Arc<Mutex<Instant>>,
+    /// Status of the writes queued by `write_async`
+    pub writes: Arc<Mutex<HashMap<u64, WriteStatus>>>,
+    /// Whether to raise RTS around each write
+    pub rs485: Arc<AtomicBool>,
+    /// Set once the port is closing
+    pub closing: Arc<AtomicBool>,
+}
+
+/// `rs485_transmit` runs `transmit` with RTS raised when `rs485` is set, as RS485 transceivers
+/// need to drive the bus, lowering it again once the written bytes have been transmitted
+pub fn rs485_transmit<T>(
+    serialport: &mut dyn SerialPort,
+    path: &str,
+    rs485: bool,
+    transmit: impl FnOnce(&mut dyn SerialPort) -> Result<T, Error>,
+) -> Result<T, Error> {
+    if !rs485 {
+        return transmit(serialport);
+    }
+    if let Err(error) = serialport.write_request_to_send(true) {
+        return Err(Error::String(format!(
+            "Failed to raise RTS of serial port {}: {}",
+            path, error
+        )));
+    }
+    let result = transmit(serialport).and_then(|value| match serialport.flush() {
+        Ok(_) => Ok(value),
+        Err(error) => Err(Error::String(format!(
+            "Failed to flush serial port {}: {}",
+            path, error
+        ))),
+    });
+    // Lowered even after a failed write, so the port doesn't keep the bus
+    match serialport.write_request_to_send(false) {
+        Ok(_) => result,
+        Err(error) => result.and(Err(Error::String(format!(
+            "Failed to lower RTS of serial port {}: {}",
+            path, error
+        )))),
+    }
+}
+
+/// `record_finished` stores the finished `status` of a write, forgetting the oldest finished writes
+/// beyond `MAX_FINISHED_WRITES`. Pending writes are kept, as they are bounded by the queue.
+fn record_finished(writes: &mut HashMap<u64, WriteStatus>, write_id: u64, status: WriteStatus) {
+    writes.insert(write_id, status);
+    let finished: Vec<u64> = writes
+        .iter()
+        .filter(|(_, status)| !matches!(status, WriteStatus::Pending))
+        .map(|(write_id, _)| *write_id)
+        .collect();
+    if finished.len() > MAX_FINISHED_WRITES {
+        // Write ids are increasing, so the smallest ids are the oldest writes
+        let mut finished = finished;
+        finished.sort_unstable();
+        for write_id in &finished[..finished.len() - MAX_FINISHED_WRITES] {
+            writes.remove(write_id);
+        }
+    }
+}
+
+/// `spawn_writer` starts the thread that writes the jobs queued on `rx` to `serial` in order,
+/// recording each outcome in `state.writes` and emitting a `write-complete` event. Once the port is
+/// closed, which sets `state.closing` and drops the queue's sender, the jobs still queued fail
+/// with `Error::Cancelled` instead of being written, and the thread exits.
+pub fn spawn_writer<R: Runtime>(
+    window: Window<R>,
+    path: String,
+    mut serial: Box<dyn SerialPort>,
+    rx: Receiver<WriteJob>,
+    state: WriterState,
+) {
+    let WriterState {
+        last_activity,
+        writes,
+        rs485,
+        closing,
+    } = state;
+    thread::spawn(move || {
+        for job in rx {
+            let result = if closing.load(Ordering::SeqCst) {
+                Err(Error::Cancelled(format!(
+                    "Serial port {} was closed before the data was written",
+                    &path
+                )))
+            } else {
+                rs485_transmit(
+                    serial.as_mut(),
+                    &path,
+                    rs485.load(Ordering::SeqCst),
+                    |serial| match serial.write_all(&job.data) {
+                        Ok(_) => Ok(()),
+                        Err(error) => Err(Error::String(format!(
+                            "Failed to write data to serial port {}: {}",
+                            &path, error
+                        ))),
+                    },
+                )
+            };
+            let status = match result {
+                Ok(_) => {
+                    if let Ok(mut last_activity) = last_activity.lock() {
+                        *last_activity = Instant::now();
+                    }
+                    WriteStatus::Done(job.data.len())
+                }
+                Err(error) => WriteStatus::Failed(error.to_string()),
+            };
+            if let Ok(mut writes) = writes.lock() {
+                record_finished(&mut writes, job.write_id, status.clone());
+            }
+            emit_event(
+                &window,
+                WRITE_COMPLETE_EVENT,
+                &path,
+                WriteResult {
+                    write_id: job.write_id,
+                    status,
+                },
+            );
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oldest_finished_writes_are_forgotten() {
+        let mut writes = HashMap::new();
+        writes.insert(0, WriteStatus::Pending);
+        for write_id in 1..=MAX_FINISHED_WRITES as u64 + 10 {
+            record_finished(&mut writes, write_id, WriteStatus::Done(1));
+        }
+        assert_eq!(writes.len(), MAX_FINISHED_WRITES + 1);
+        assert!(matches!(writes.get(&0), Some(WriteStatus::Pending)));
+        assert!(!writes.contains_key(&10));
+        assert!(writes.contains_key(&11));
+    }
+}